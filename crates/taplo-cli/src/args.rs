@@ -15,6 +15,9 @@ pub struct TaploArgs {
     /// Enable logging spans.
     #[clap(long, global = true)]
     pub log_spans: bool,
+    /// Path to a TOML file with translated message templates by their codes.
+    #[clap(long, global = true)]
+    pub messages: Option<PathBuf>,
     #[clap(subcommand)]
     pub cmd: TaploCommand,
 }
@@ -67,6 +70,53 @@ pub enum TaploCommand {
     },
     /// Extract a value from the given TOML document.
     Get(GetCommand),
+    /// Replace values in TOML documents.
+    ///
+    /// Only the replaced values are changed, everything else in the documents is kept as it is.
+    /// The changes are printed as a diff and the files are modified in-place unless "--dry-run" is given.
+    ///
+    /// Example:
+    ///
+    /// taplo replace --key 'dependencies.*.git' --match 'github\.com' --with 'gitlab.com' Cargo.toml
+    Replace(ReplaceCommand),
+    /// Operations with JSON schemas.
+    Schema {
+        #[clap(subcommand)]
+        cmd: SchemaCommand,
+    },
+    /// Merge TOML documents key by key, can be used as a git merge driver.
+    ///
+    /// The merge result is written to the path of the current version,
+    /// if there were conflicts the operation fails and the file is left unchanged.
+    ///
+    /// Example configuration:
+    ///
+    /// git config merge.taplo.driver "taplo merge-driver %O %A %B %P"
+    ///
+    /// echo "*.toml merge=taplo" >> .gitattributes
+    MergeDriver(MergeDriverCommand),
+    /// Show the semantic differences between two TOML documents,
+    /// can be used as a git difftool or external diff driver.
+    ///
+    /// Example configuration:
+    ///
+    /// git config difftool.taplo.cmd 'taplo git-difftool "$LOCAL" "$REMOTE"'
+    ///
+    /// git config diff.taplo.command "taplo git-difftool"
+    ///
+    /// echo "*.toml diff=taplo" >> .gitattributes
+    GitDifftool(GitDifftoolCommand),
+    /// Merge TOML documents key by key, can be used as a git mergetool.
+    ///
+    /// The merge result is written to the merged path,
+    /// if there were conflicts the operation fails and the file is left unchanged.
+    ///
+    /// Example configuration:
+    ///
+    /// git config mergetool.taplo.cmd 'taplo git-mergetool "$BASE" "$LOCAL" "$REMOTE" "$MERGED"'
+    ///
+    /// git config mergetool.taplo.trustExitCode true
+    GitMergetool(GitMergetoolCommand),
     /// Start a decoder for `toml-test` (https://github.com/BurntSushi/toml-test).
     #[cfg(feature = "toml-test")]
     TomlTest {},
@@ -95,6 +145,10 @@ pub struct FormatCommand {
     #[clap(long)]
     pub diff: bool,
 
+    /// Keep running and format the files again whenever they change.
+    #[clap(long)]
+    pub watch: bool,
+
     /// Paths or glob patterns to TOML documents.
     ///
     /// If the only argument is "-", the standard input will be used.
@@ -108,6 +162,84 @@ pub struct FormatCommand {
     pub stdin_filepath: Option<String>,
 }
 
+#[derive(Clone, Args)]
+pub struct ReplaceCommand {
+    #[clap(flatten)]
+    pub general: GeneralArgs,
+
+    /// Dotted keys of the values to replace, every key can be a glob pattern, e.g. "dependencies.*.git".
+    #[clap(long, short)]
+    pub key: String,
+
+    /// A regular expression, only the matching parts of the values are replaced.
+    ///
+    /// Strings are matched by their contents, other values by their TOML text.
+    /// By default the entire values are replaced.
+    #[clap(long = "match", short)]
+    pub pattern: Option<String>,
+
+    /// The replacement, "$1" or "${name}" refer to the groups of the regular expression.
+    ///
+    /// The replacement of a string is escaped if needed, other values are replaced with the TOML text as-is.
+    #[clap(long = "with", short)]
+    pub replacement: String,
+
+    /// Only print the changes without modifying the files.
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Paths or glob patterns to TOML documents.
+    pub files: Vec<String>,
+}
+
+#[derive(Clone, Args)]
+pub struct MergeDriverCommand {
+    /// Path to the common ancestor's version of the document (`%O`).
+    pub base: PathBuf,
+
+    /// Path to the current version of the document (`%A`).
+    ///
+    /// The merged document is written here if there were no conflicts.
+    pub ours: PathBuf,
+
+    /// Path to the other branch's version of the document (`%B`).
+    pub theirs: PathBuf,
+
+    /// The path of the document in the repository (`%P`), only used for reporting.
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Args)]
+pub struct GitDifftoolCommand {
+    /// Path to the old version of the document (`$LOCAL`),
+    /// or the path of the document in the repository when used as an external diff driver.
+    pub first: PathBuf,
+
+    /// Path to the new version of the document (`$REMOTE`),
+    /// or the path to the old version when used as an external diff driver.
+    pub second: PathBuf,
+
+    /// The remaining 5 arguments git passes to external diff drivers:
+    /// `old-hex old-mode new-file new-hex new-mode`.
+    #[clap(number_of_values = 5)]
+    pub external: Vec<PathBuf>,
+}
+
+#[derive(Clone, Args)]
+pub struct GitMergetoolCommand {
+    /// Path to the common ancestor's version of the document (`$BASE`).
+    pub base: PathBuf,
+
+    /// Path to the current branch's version of the document (`$LOCAL`).
+    pub local: PathBuf,
+
+    /// Path to the other branch's version of the document (`$REMOTE`).
+    pub remote: PathBuf,
+
+    /// Path the merged document is written to if there were no conflicts (`$MERGED`).
+    pub merged: PathBuf,
+}
+
 #[derive(Clone, Subcommand)]
 pub enum LspCommand {
     /// Run the language server and listen on a TCP address.
@@ -128,6 +260,24 @@ pub enum ConfigCommand {
     Schema,
 }
 
+#[derive(Clone, Subcommand)]
+pub enum SchemaCommand {
+    /// Generate a JSON schema from an example TOML document.
+    ///
+    /// All keys in the example are required, the items of arrays
+    /// are merged into a single schema.
+    ///
+    /// The schema is only a starting point and should be reviewed,
+    /// descriptions, default values and stricter constraints have to be added by hand.
+    Infer(SchemaInferCommand),
+}
+
+#[derive(Clone, Args)]
+pub struct SchemaInferCommand {
+    /// Path to the example TOML document, if omitted the standard input will be used.
+    pub file_path: Option<PathBuf>,
+}
+
 #[derive(Clone, Args)]
 pub struct LintCommand {
     #[clap(flatten)]
@@ -151,6 +301,10 @@ pub struct LintCommand {
     #[clap(long)]
     pub no_schema: bool,
 
+    /// Keep running and lint the files again whenever they change.
+    #[clap(long)]
+    pub watch: bool,
+
     /// Paths or glob patterns to TOML documents.
     ///
     /// If the only argument is "-", the standard input will be used.
@@ -14,7 +14,20 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 impl<E: Environment> Taplo<E> {
     pub async fn execute_format(&mut self, cmd: FormatCommand) -> Result<(), anyhow::Error> {
         if matches!(cmd.files.get(0).map(|it| it.as_str()), Some("-")) {
+            if cmd.watch {
+                return Err(anyhow!("the standard input cannot be watched"));
+            }
+
             self.format_stdin(cmd).await
+        } else if cmd.watch {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.watch_format(cmd).await
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                Err(anyhow!("the `--watch` flag is not available in this build"))
+            }
         } else {
             self.format_files(cmd).await
         }
@@ -81,7 +94,7 @@ impl<E: Environment> Taplo<E> {
     }
 
     #[cfg(target_arch = "wasm32")]
-    async fn print_diff(
+    pub(crate) async fn print_diff(
         &self,
         _path: impl AsRef<Path>,
         _original: &str,
@@ -92,7 +105,7 @@ impl<E: Environment> Taplo<E> {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    async fn print_diff(
+    pub(crate) async fn print_diff(
         &self,
         path: impl AsRef<Path>,
         original: &str,
@@ -217,10 +230,19 @@ impl<E: Environment> Taplo<E> {
             .collect_files(&cwd, &config, mem::take(&mut cmd.files).into_iter())
             .await?;
 
+        self.format_paths(&config, &cmd, files).await
+    }
+
+    pub(crate) async fn format_paths(
+        &self,
+        config: &Config,
+        cmd: &FormatCommand,
+        files: Vec<PathBuf>,
+    ) -> Result<(), anyhow::Error> {
         let mut result = Ok(());
 
         for path in files {
-            let format_opts = self.format_options(&config, &cmd, &path)?;
+            let format_opts = self.format_options(config, cmd, &path)?;
 
             let f = self.env.read_file(&path).await?;
             let source = String::from_utf8_lossy(&f).into_owned();
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use crate::{
+    args::{GitDifftoolCommand, GitMergetoolCommand, MergeDriverCommand},
+    Taplo,
+};
+use anyhow::anyhow;
+use codespan_reporting::files::SimpleFile;
+use taplo::{
+    dom::{self, Node},
+    parser,
+};
+use taplo_common::environment::Environment;
+
+impl<E: Environment> Taplo<E> {
+    pub async fn execute_merge_driver(&self, cmd: MergeDriverCommand) -> Result<(), anyhow::Error> {
+        let display_path = cmd
+            .path
+            .clone()
+            .unwrap_or_else(|| cmd.ours.to_string_lossy().into_owned());
+
+        self.merge_files(&cmd.base, &cmd.ours, &cmd.theirs, &cmd.ours, &display_path)
+            .await
+    }
+
+    pub async fn execute_git_mergetool(
+        &self,
+        cmd: GitMergetoolCommand,
+    ) -> Result<(), anyhow::Error> {
+        let display_path = cmd.merged.to_string_lossy().into_owned();
+
+        self.merge_files(
+            &cmd.base,
+            &cmd.local,
+            &cmd.remote,
+            &cmd.merged,
+            &display_path,
+        )
+        .await
+    }
+
+    pub async fn execute_git_difftool(&self, cmd: GitDifftoolCommand) -> Result<(), anyhow::Error> {
+        let (old_path, new_path, old_name, new_name) = match cmd.external.as_slice() {
+            [_, _, new, _, _] => (
+                &cmd.second,
+                new,
+                format!("a/{}", cmd.first.to_string_lossy()),
+                format!("b/{}", cmd.first.to_string_lossy()),
+            ),
+            _ => (
+                &cmd.first,
+                &cmd.second,
+                cmd.first.to_string_lossy().into_owned(),
+                cmd.second.to_string_lossy().into_owned(),
+            ),
+        };
+
+        let (_, old) = self.load_document(old_path).await?;
+        let (_, new) = self.load_document(new_path).await?;
+
+        self.print_changes(&old_name, &new_name, dom::diff(&old, &new))
+            .await
+    }
+
+    async fn merge_files(
+        &self,
+        base: &Path,
+        ours: &Path,
+        theirs: &Path,
+        output: &Path,
+        display_path: &str,
+    ) -> Result<(), anyhow::Error> {
+        let (_, base) = self.load_document(base).await?;
+        let (ours_source, ours) = self.load_document(ours).await?;
+        let (theirs_source, theirs) = self.load_document(theirs).await?;
+
+        let merge = dom::merge3(&base, &ours, &theirs)?;
+
+        // The merged document has no conflict markers,
+        // so the output is left as it is if there were conflicts.
+        if !merge.is_clean() {
+            self.print_merge_conflicts(
+                display_path,
+                &ours_source,
+                &theirs_source,
+                merge.conflicts(),
+            )
+            .await?;
+
+            return Err(anyhow!("merge conflicts found"));
+        }
+
+        self.env
+            .write_file(output, merge.to_string().as_bytes())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read and parse a document that must not contain syntax errors.
+    pub(crate) async fn load_document(&self, path: &Path) -> Result<(String, Node), anyhow::Error> {
+        let source = String::from_utf8(self.env.read_file(path).await?)?;
+        let parse = parser::parse(&source);
+
+        self.print_parse_errors(
+            &SimpleFile::new(&*path.to_string_lossy(), source.as_str()),
+            &parse.errors,
+        )
+        .await?;
+
+        if !parse.errors.is_empty() {
+            return Err(anyhow!("syntax errors found"));
+        }
+
+        let dom = parse.into_dom();
+
+        Ok((source, dom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{args::MergeDriverCommand, Taplo};
+    use std::fs;
+    use taplo_common::environment::native::NativeEnvironment;
+
+    #[tokio::test]
+    async fn merge_driver_keeps_ours_on_conflict() {
+        let dir = std::env::temp_dir().join(format!("taplo-merge-driver-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let base = dir.join("base.toml");
+        let ours = dir.join("ours.toml");
+        let theirs = dir.join("theirs.toml");
+
+        fs::write(&base, "a = 1\nb = 1\n").unwrap();
+        fs::write(&ours, "a = 2\nb = 1\n").unwrap();
+        fs::write(&theirs, "a = 3\nb = 2\n").unwrap();
+
+        let taplo = Taplo::new(NativeEnvironment::new());
+        let res = taplo
+            .execute_merge_driver(MergeDriverCommand {
+                base,
+                ours: ours.clone(),
+                theirs,
+                path: None,
+            })
+            .await;
+
+        let written = fs::read_to_string(&ours).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(res.is_err());
+        assert_eq!(written, "a = 2\nb = 1\n");
+    }
+}
@@ -54,7 +54,20 @@ impl<E: Environment> Taplo<E> {
         }
 
         if matches!(cmd.files.get(0).map(|it| it.as_str()), Some("-")) {
+            if cmd.watch {
+                return Err(anyhow!("the standard input cannot be watched"));
+            }
+
             self.lint_stdin(cmd).await
+        } else if cmd.watch {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.watch_lint(cmd).await
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                Err(anyhow!("the `--watch` flag is not available in this build"))
+            }
         } else {
             self.lint_files(cmd).await
         }
@@ -92,7 +105,7 @@ impl<E: Environment> Taplo<E> {
         result
     }
 
-    async fn lint_file(&self, file: &Path) -> Result<(), anyhow::Error> {
+    pub(crate) async fn lint_file(&self, file: &Path) -> Result<(), anyhow::Error> {
         let source = self.env.read_file(file).await?;
         let source = String::from_utf8(source)?;
         self.lint_source(&*file.to_string_lossy(), &source).await
@@ -101,24 +114,37 @@ impl<E: Environment> Taplo<E> {
     async fn lint_source(&self, file_path: &str, source: &str) -> Result<(), anyhow::Error> {
         let parse = parser::parse(source);
 
-        self.print_parse_errors(&SimpleFile::new(file_path, source), &parse.errors)
+        let config = self.config.as_ref().unwrap();
+        let filter = config.diagnostic_filter(Path::new(file_path), &parse.directives);
+
+        let has_errors = self
+            .print_filtered_parse_errors(
+                &SimpleFile::new(file_path, source),
+                &parse.errors,
+                &filter,
+            )
             .await?;
 
-        if !parse.errors.is_empty() {
+        if has_errors {
             return Err(anyhow!("syntax errors found"));
         }
 
         let dom = parse.into_dom();
 
         if let Err(errors) = dom.validate() {
-            self.print_semantic_errors(&SimpleFile::new(file_path, source), errors)
+            let has_errors = self
+                .print_filtered_semantic_errors(
+                    &SimpleFile::new(file_path, source),
+                    errors,
+                    &filter,
+                )
                 .await?;
 
-            return Err(anyhow!("semantic errors found"));
+            if has_errors {
+                return Err(anyhow!("semantic errors found"));
+            }
         }
 
-        let config = self.config.as_ref().unwrap();
-
         if !config.is_schema_enabled(Path::new(file_path)) {
             tracing::debug!("schema validation disabled for config file");
             return Ok(());
@@ -138,15 +164,25 @@ impl<E: Environment> Taplo<E> {
                 "using schema"
             );
 
+            let deprecations = self
+                .schemas
+                .deprecations(&schema_association.url, &dom)
+                .await?;
+
+            let deprecated = self
+                .print_deprecations(&SimpleFile::new(file_path, source), &deprecations, &filter)
+                .await?;
+
             let errors = self
                 .schemas
                 .validate_root(&schema_association.url, &dom)
                 .await?;
 
-            if !errors.is_empty() {
-                self.print_schema_errors(&SimpleFile::new(file_path, source), &errors)
-                    .await?;
+            let invalid = self
+                .print_schema_errors(&SimpleFile::new(file_path, source), &errors, &filter)
+                .await?;
 
+            if deprecated || invalid {
                 return Err(anyhow!("schema validation failed"));
             }
         }
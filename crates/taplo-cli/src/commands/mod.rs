@@ -1,3 +1,4 @@
+use anyhow::Context;
 use taplo_common::environment::Environment;
 
 use crate::{
@@ -7,10 +8,15 @@ use crate::{
 
 mod config;
 mod format;
+mod git;
 mod lint;
 #[cfg(feature = "lsp")]
 mod lsp;
 mod queries;
+mod replace;
+mod schema;
+#[cfg(not(target_arch = "wasm32"))]
+mod watch;
 
 #[cfg(feature = "toml-test")]
 mod toml_test;
@@ -23,6 +29,15 @@ impl<E: Environment> Taplo<E> {
             crate::args::Colors::Never => false,
         };
 
+        if let Some(path) = &taplo.messages {
+            let messages = self
+                .env
+                .read_file(path)
+                .await
+                .with_context(|| format!("failed to read messages from {path:?}"))?;
+            self.catalog = toml::from_slice(&messages).context("invalid messages file")?;
+        }
+
         match taplo.cmd {
             TaploCommand::Format(fmt) => self.execute_format(fmt).await,
             TaploCommand::Lsp { cmd } => {
@@ -42,6 +57,11 @@ impl<E: Environment> Taplo<E> {
             TaploCommand::Lint(cmd) => self.execute_lint(cmd).await,
             TaploCommand::Config { cmd } => self.execute_config(cmd).await,
             TaploCommand::Get(cmd) => self.execute_get(cmd).await,
+            TaploCommand::Replace(cmd) => self.execute_replace(cmd).await,
+            TaploCommand::Schema { cmd } => self.execute_schema(cmd).await,
+            TaploCommand::MergeDriver(cmd) => self.execute_merge_driver(cmd).await,
+            TaploCommand::GitDifftool(cmd) => self.execute_git_difftool(cmd).await,
+            TaploCommand::GitMergetool(cmd) => self.execute_git_mergetool(cmd).await,
         }
     }
 }
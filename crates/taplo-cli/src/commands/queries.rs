@@ -243,6 +243,7 @@ fn extract_value(node: &Node) -> Result<String, anyhow::Error> {
         Node::Integer(i) => i.value().to_string(),
         Node::Float(f) => f.value().to_string(),
         Node::Date(d) => d.value().to_string(),
+        Node::Unknown(u) => u.text(),
         Node::Invalid(_) => "".into(),
     })
 }
@@ -0,0 +1,136 @@
+use std::mem;
+
+use crate::{args::ReplaceCommand, Taplo};
+use anyhow::anyhow;
+use codespan_reporting::files::SimpleFile;
+use regex::Regex;
+use taplo::{
+    dom::{
+        self,
+        node::{DomNode, StrRepr},
+        Keys, Node,
+    },
+    parser,
+    util::escape_for,
+};
+use taplo_common::environment::Environment;
+
+impl<E: Environment> Taplo<E> {
+    pub async fn execute_replace(&mut self, mut cmd: ReplaceCommand) -> Result<(), anyhow::Error> {
+        let keys = cmd
+            .key
+            .trim_start_matches('.')
+            .parse::<Keys>()
+            .map_err(|err| anyhow!("invalid key pattern: {err}"))?;
+
+        let pattern = cmd
+            .pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|err| anyhow!("invalid regular expression: {err}"))?;
+
+        let config = self.load_config(&cmd.general).await?;
+
+        let cwd = self
+            .env
+            .cwd_normalized()
+            .ok_or_else(|| anyhow!("could not figure the current working directory"))?;
+
+        let files = self
+            .collect_files(&cwd, &config, mem::take(&mut cmd.files).into_iter())
+            .await?;
+
+        let mut result = Ok(());
+
+        for path in files {
+            let f = self.env.read_file(&path).await?;
+            let source = String::from_utf8_lossy(&f).into_owned();
+
+            let p = parser::parse(&source);
+
+            if !p.errors.is_empty() {
+                self.print_parse_errors(
+                    &SimpleFile::new(&*path.to_string_lossy(), source.as_str()),
+                    &p.errors,
+                )
+                .await?;
+
+                result = Err(anyhow!("some files were not changed due to syntax errors"));
+                continue;
+            }
+
+            let replaced = dom::replace_values(p.into_dom(), keys.clone(), |_, node| {
+                replace_value(node, pattern.as_ref(), &cmd.replacement)
+            })
+            .map_err(|err| anyhow!("failed to replace values: {err}"))?
+            .to_string();
+
+            if replaced == source {
+                continue;
+            }
+
+            // Non-string replacements are not checked otherwise.
+            let errors = parser::parse(&replaced).errors;
+            if !errors.is_empty() {
+                self.print_parse_errors(
+                    &SimpleFile::new(&*path.to_string_lossy(), replaced.as_str()),
+                    &errors,
+                )
+                .await?;
+
+                tracing::error!(?path, "the replacements would result in invalid TOML");
+                result = Err(anyhow!("some files were not changed due to syntax errors"));
+                continue;
+            }
+
+            self.print_diff(&path, &source, &replaced).await?;
+
+            if !cmd.dry_run {
+                self.env.write_file(&path, replaced.as_bytes()).await?;
+            }
+        }
+
+        result
+    }
+}
+
+/// The new TOML text of the value, if it matches the pattern.
+fn replace_value(node: &Node, pattern: Option<&Regex>, replacement: &str) -> Option<String> {
+    let text = match node {
+        Node::Str(s) => s.value().to_string(),
+        _ => node.syntax()?.to_string(),
+    };
+
+    let replaced = match pattern {
+        Some(pattern) if pattern.is_match(&text) => {
+            pattern.replace_all(&text, replacement).into_owned()
+        }
+        Some(_) => return None,
+        None => replacement.to_string(),
+    };
+
+    if replaced == text {
+        return None;
+    }
+
+    match node {
+        Node::Str(s) => Some(toml_string(&replaced, s.repr())),
+        _ => Some(replaced),
+    }
+}
+
+/// The string in TOML, of the same kind as the original if possible.
+fn toml_string(s: &str, repr: StrRepr) -> String {
+    let fallback = match repr {
+        StrRepr::MultiLine | StrRepr::MultiLineLiteral => StrRepr::MultiLine,
+        StrRepr::Basic | StrRepr::Literal => StrRepr::Basic,
+    };
+
+    let (repr, escaped) = match escape_for(s, repr) {
+        Some(escaped) => (repr, escaped),
+        None => (fallback, escape_for(s, fallback).unwrap_or_default()),
+    };
+
+    format!("{quotes}{escaped}{quotes}", quotes = repr.quotes())
+}
@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use crate::{
+    args::{SchemaCommand, SchemaInferCommand},
+    Taplo,
+};
+use anyhow::anyhow;
+use codespan_reporting::files::SimpleFile;
+use taplo::parser;
+use taplo_common::{environment::Environment, schema};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+impl<E: Environment> Taplo<E> {
+    pub async fn execute_schema(&self, cmd: SchemaCommand) -> Result<(), anyhow::Error> {
+        match cmd {
+            SchemaCommand::Infer(cmd) => self.execute_schema_infer(cmd).await,
+        }
+    }
+
+    async fn execute_schema_infer(&self, cmd: SchemaInferCommand) -> Result<(), anyhow::Error> {
+        let source = match &cmd.file_path {
+            Some(p) => String::from_utf8(self.env.read_file(p).await?)?,
+            None => {
+                let mut stdin = self.env.stdin();
+                let mut s = String::new();
+                stdin.read_to_string(&mut s).await?;
+                s
+            }
+        };
+
+        let parse = parser::parse(&source);
+
+        let file_path = cmd
+            .file_path
+            .as_ref()
+            .map(|p| p.to_string_lossy())
+            .unwrap_or(Cow::Borrowed("-"));
+
+        self.print_parse_errors(&SimpleFile::new(&file_path, &source), &parse.errors)
+            .await?;
+
+        if !parse.errors.is_empty() {
+            return Err(anyhow!("syntax errors found"));
+        }
+
+        let node = parse.into_dom();
+
+        if let Err(errors) = node.validate() {
+            self.print_semantic_errors(&SimpleFile::new(&file_path, &source), errors)
+                .await?;
+
+            return Err(anyhow!("semantic errors found"));
+        }
+
+        let mut stdout = self.env.stdout();
+        stdout
+            .write_all(&serde_json::to_vec_pretty(&schema::infer(&node))?)
+            .await?;
+        stdout.write_all(b"\n").await?;
+        stdout.flush().await?;
+
+        Ok(())
+    }
+}
@@ -71,7 +71,7 @@ impl TomlTestType {
             },
             Node::Array(_) => None,
             Node::Table(_) => None,
-            Node::Invalid(_) => unreachable!(),
+            Node::Unknown(_) | Node::Invalid(_) => unreachable!(),
         }
     }
 }
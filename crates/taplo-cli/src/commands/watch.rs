@@ -0,0 +1,141 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    args::{FormatCommand, LintCommand},
+    Taplo,
+};
+use anyhow::anyhow;
+use itertools::Itertools;
+use taplo_common::{config::Config, environment::Environment, util::Normalize};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// Changes within this interval are handled together,
+/// editors often write a file in multiple steps.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The changed files that match the patterns of the command.
+struct ChangedFiles {
+    changes: UnboundedReceiver<PathBuf>,
+    patterns: Vec<glob::Pattern>,
+}
+
+impl ChangedFiles {
+    /// Waits for the next changes and returns the files that should be checked again.
+    async fn next(&mut self, config: &Config) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let first = self
+            .changes
+            .recv()
+            .await
+            .ok_or_else(|| anyhow!("the file watcher stopped"))?;
+
+        tokio::time::sleep(DEBOUNCE_INTERVAL).await;
+
+        let mut changed = vec![first];
+        while let Ok(path) = self.changes.try_recv() {
+            changed.push(path);
+        }
+
+        Ok(changed
+            .into_iter()
+            .map(Normalize::normalize)
+            .unique()
+            .filter(|path| {
+                self.patterns.iter().any(|p| p.matches_path(path)) && config.is_included(path)
+            })
+            .collect())
+    }
+}
+
+impl<E: Environment> Taplo<E> {
+    /// Formats the files whenever they change until the process is stopped.
+    pub(crate) async fn watch_format(&mut self, cmd: FormatCommand) -> Result<(), anyhow::Error> {
+        let config = self.load_config(&cmd.general).await?;
+
+        let cwd = self
+            .env
+            .cwd_normalized()
+            .ok_or_else(|| anyhow!("could not figure the current working directory"))?;
+
+        let mut files = self
+            .collect_files(&cwd, &config, cmd.files.iter().cloned())
+            .await?;
+        let mut changed = self.watch_files(&cwd, &config, &cmd.files, &files)?;
+
+        tracing::info!("watching for changes");
+
+        loop {
+            for path in files {
+                // Formatting the file triggers another change,
+                // but it is only written again if it is not formatted.
+                match self.format_paths(&config, &cmd, vec![path.clone()]).await {
+                    Ok(()) => tracing::info!(?path, "file checked"),
+                    Err(error) => tracing::error!(%error, ?path, "invalid file"),
+                }
+            }
+
+            files = changed.next(&config).await?;
+        }
+    }
+
+    /// Lints the files whenever they change until the process is stopped.
+    pub(crate) async fn watch_lint(&mut self, cmd: LintCommand) -> Result<(), anyhow::Error> {
+        let config = self.load_config(&cmd.general).await?;
+
+        let cwd = self
+            .env
+            .cwd_normalized()
+            .ok_or_else(|| anyhow!("could not figure the current working directory"))?;
+
+        let mut files = self
+            .collect_files(&cwd, &config, cmd.files.iter().cloned())
+            .await?;
+        let mut changed = self.watch_files(&cwd, &config, &cmd.files, &files)?;
+
+        tracing::info!("watching for changes");
+
+        loop {
+            for path in files {
+                match self.lint_file(&path).await {
+                    Ok(()) => tracing::info!(?path, "file checked"),
+                    Err(error) => tracing::error!(%error, ?path, "invalid file"),
+                }
+            }
+
+            files = changed.next(&config).await?;
+        }
+    }
+
+    /// Watches the working directory and the directories of the files outside of it.
+    fn watch_files(
+        &self,
+        cwd: &Path,
+        config: &Config,
+        arg_patterns: &[String],
+        files: &[PathBuf],
+    ) -> Result<ChangedFiles, anyhow::Error> {
+        let dirs: Vec<PathBuf> = std::iter::once(cwd.to_path_buf())
+            .chain(
+                files
+                    .iter()
+                    .filter(|path| !path.starts_with(cwd))
+                    .filter_map(|path| path.parent().map(Path::to_path_buf)),
+            )
+            .unique()
+            .collect();
+
+        let patterns = self
+            .file_patterns(cwd, config, arg_patterns.iter().cloned())?
+            .iter()
+            .map(String::as_str)
+            .map(glob::Pattern::new)
+            .collect::<Result<_, _>>()?;
+
+        Ok(ChangedFiles {
+            changes: self.env.watch(&dirs)?,
+            patterns,
+        })
+    }
+}
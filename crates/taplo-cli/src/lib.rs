@@ -5,6 +5,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use taplo::messages::Catalog;
 use taplo_common::{config::Config, environment::Environment, schema::Schemas, util::Normalize};
 
 pub mod args;
@@ -16,6 +17,7 @@ pub struct Taplo<E: Environment> {
     colors: bool,
     schemas: Schemas<E>,
     config: Option<Arc<Config>>,
+    catalog: Catalog,
 }
 
 impl<E: Environment> Taplo<E> {
@@ -33,6 +35,7 @@ impl<E: Environment> Taplo<E> {
             schemas: Schemas::new(env.clone(), http),
             colors: env.atty_stderr(),
             config: None,
+            catalog: Catalog::default(),
             env,
         }
     }
@@ -91,6 +94,38 @@ impl<E: Environment> Taplo<E> {
         config: &Config,
         arg_patterns: impl Iterator<Item = String>,
     ) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let patterns = self.file_patterns(cwd, config, arg_patterns)?;
+
+        let files = patterns
+            .into_iter()
+            .map(|pat| self.env.glob_files_normalized(&pat))
+            .collect::<Result<Vec<_>, _>>()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let total = files.len();
+
+        let files = files
+            .into_iter()
+            .filter(|path| config.is_included(path))
+            .collect::<Vec<_>>();
+
+        let excluded = total - files.len();
+
+        tracing::info!(total, excluded, "found files");
+
+        Ok(files)
+    }
+
+    /// The absolute glob patterns of the files, either from the arguments or the configuration.
+    pub(crate) fn file_patterns(
+        &self,
+        cwd: &Path,
+        config: &Config,
+        arg_patterns: impl Iterator<Item = String>,
+    ) -> Result<Vec<String>, anyhow::Error> {
         let mut patterns: Vec<String> = arg_patterns
             .map(|pat| {
                 if !self.env.is_absolute(Path::new(&pat)) {
@@ -112,33 +147,11 @@ impl<E: Environment> Taplo<E> {
             };
         };
 
-        let patterns = patterns
+        Ok(patterns
             .into_iter()
             .unique()
             .map(|p| glob::Pattern::new(&p).map(|_| p))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let files = patterns
-            .into_iter()
-            .map(|pat| self.env.glob_files_normalized(&pat))
-            .collect::<Result<Vec<_>, _>>()
-            .into_iter()
-            .flatten()
-            .flatten()
-            .collect::<Vec<_>>();
-
-        let total = files.len();
-
-        let files = files
-            .into_iter()
-            .filter(|path| config.is_included(path))
-            .collect::<Vec<_>>();
-
-        let excluded = total - files.len();
-
-        tracing::info!(total, excluded, "found files");
-
-        Ok(files)
+            .collect::<Result<Vec<_>, _>>()?)
     }
 }
 
@@ -1,7 +1,7 @@
 use crate::Taplo;
 use codespan_reporting::{
-    diagnostic::{Diagnostic, Label},
-    files::SimpleFile,
+    diagnostic::{self, Diagnostic, Label},
+    files::{SimpleFile, SimpleFiles},
     term::{
         self,
         termcolor::{Ansi, NoColor},
@@ -9,8 +9,22 @@ use codespan_reporting::{
 };
 use itertools::Itertools;
 use std::ops::Range;
-use taplo::{dom, parser, rowan::TextRange};
-use taplo_common::{environment::Environment, schema::NodeValidationError};
+use taplo::{
+    diagnostics::{self, DiagnosticFilter, Severity},
+    dom::{
+        self,
+        diff::{is_container, Change},
+        merge::Conflict,
+        Keys, Node,
+    },
+    messages::Message,
+    parser,
+    rowan::TextRange,
+};
+use taplo_common::{
+    environment::Environment,
+    schema::{Deprecation, NodeValidationError},
+};
 use tokio::io::AsyncWriteExt;
 
 impl<E: Environment> Taplo<E> {
@@ -19,16 +33,35 @@ impl<E: Environment> Taplo<E> {
         file: &SimpleFile<&str, &str>,
         errors: &[parser::Error],
     ) -> Result<(), anyhow::Error> {
+        self.print_filtered_parse_errors(file, errors, &DiagnosticFilter::default())
+            .await?;
+        Ok(())
+    }
+
+    /// Prints the syntax errors that are not suppressed by the filter with their
+    /// configured severities, returns whether any of them is an error.
+    pub(crate) async fn print_filtered_parse_errors(
+        &self,
+        file: &SimpleFile<&str, &str>,
+        errors: &[parser::Error],
+        filter: &DiagnosticFilter,
+    ) -> Result<bool, anyhow::Error> {
         let mut out_diag = Vec::<u8>::new();
+        let mut has_errors = false;
 
         let config = codespan_reporting::term::Config::default();
 
         for error in errors.iter().unique_by(|e| e.range) {
-            let diag = Diagnostic::error()
-                .with_message("invalid TOML")
-                .with_labels(Vec::from([
-                    Label::primary((), std_range(error.range)).with_message(&error.message)
-                ]));
+            let severity = match filter.diagnostic_severity(&diagnostics::Diagnostic::from(error)) {
+                Some(s) => s,
+                None => continue,
+            };
+            has_errors |= severity == Severity::Error;
+
+            let diag = Diagnostic::new(diagnostic_severity(severity))
+                .with_message(self.catalog.format(&Message::new("invalid-toml")))
+                .with_labels(Vec::from([Label::primary((), std_range(error.range))
+                    .with_message(self.catalog.format(&error.message))]));
 
             if self.colors {
                 term::emit(&mut Ansi::new(&mut out_diag), &config, file, &diag)?;
@@ -42,7 +75,7 @@ impl<E: Environment> Taplo<E> {
         stderr.write_all(&out_diag).await?;
         stderr.flush().await?;
 
-        Ok(())
+        Ok(has_errors)
     }
 
     pub(crate) async fn print_semantic_errors(
@@ -50,56 +83,44 @@ impl<E: Environment> Taplo<E> {
         file: &SimpleFile<&str, &str>,
         errors: impl Iterator<Item = dom::Error>,
     ) -> Result<(), anyhow::Error> {
+        self.print_filtered_semantic_errors(file, errors, &DiagnosticFilter::default())
+            .await?;
+        Ok(())
+    }
+
+    /// Prints the errors that are not suppressed by the filter with their
+    /// configured severities, returns whether any of them is an error.
+    pub(crate) async fn print_filtered_semantic_errors(
+        &self,
+        file: &SimpleFile<&str, &str>,
+        errors: impl Iterator<Item = dom::Error>,
+        filter: &DiagnosticFilter,
+    ) -> Result<bool, anyhow::Error> {
         let mut out_diag = Vec::<u8>::new();
+        let mut has_errors = false;
 
         let config = codespan_reporting::term::Config::default();
 
         for error in errors {
-            let diag = match &error {
-                dom::Error::ConflictingKeys { key, other } => Diagnostic::error()
-                    .with_message(error.to_string())
-                    .with_labels(Vec::from([
-                        Label::primary((), std_range(key.text_ranges().next().unwrap()))
-                            .with_message("duplicate key"),
-                        Label::secondary((), std_range(other.text_ranges().next().unwrap()))
-                            .with_message("duplicate found here"),
-                    ])),
-                dom::Error::ExpectedArrayOfTables {
-                    not_array_of_tables,
-                    required_by,
-                } => Diagnostic::error()
-                    .with_message(error.to_string())
-                    .with_labels(Vec::from([
-                        Label::primary(
-                            (),
-                            std_range(not_array_of_tables.text_ranges().next().unwrap()),
-                        )
-                        .with_message("expected array of tables"),
-                        Label::secondary((), std_range(required_by.text_ranges().next().unwrap()))
-                            .with_message("required by this key"),
-                    ])),
-                dom::Error::ExpectedTable {
-                    not_table,
-                    required_by,
-                } => Diagnostic::error()
-                    .with_message(error.to_string())
-                    .with_labels(Vec::from([
-                        Label::primary((), std_range(not_table.text_ranges().next().unwrap()))
-                            .with_message("expected table"),
-                        Label::secondary((), std_range(required_by.text_ranges().next().unwrap()))
-                            .with_message("required by this key"),
-                    ])),
-                dom::Error::InvalidEscapeSequence { string } => Diagnostic::error()
-                    .with_message(error.to_string())
-                    .with_labels(Vec::from([Label::primary(
-                        (),
-                        std_range(string.text_range()),
-                    )
-                    .with_message("the string contains invalid escape sequences")])),
-                _ => {
-                    unreachable!("this is a bug")
-                }
+            let diagnostic = diagnostics::Diagnostic::from(error);
+
+            let severity = match filter.diagnostic_severity(&diagnostic) {
+                Some(s) => s,
+                None => continue,
             };
+            has_errors |= severity == Severity::Error;
+
+            let diag = Diagnostic::new(diagnostic_severity(severity))
+                .with_code(diagnostic.code.name())
+                .with_message(self.catalog.format(&diagnostic.message))
+                .with_labels(
+                    std::iter::once(Label::primary((), std_range(diagnostic.primary_span)))
+                        .chain(diagnostic.related_spans.iter().map(|related| {
+                            Label::secondary((), std_range(related.span))
+                                .with_message(self.catalog.format(&related.message))
+                        }))
+                        .collect(),
+                );
 
             if self.colors {
                 term::emit(&mut Ansi::new(&mut out_diag), &config, file, &diag)?;
@@ -110,22 +131,33 @@ impl<E: Environment> Taplo<E> {
         let mut stderr = self.env.stderr();
         stderr.write_all(&out_diag).await?;
         stderr.flush().await?;
-        Ok(())
+        Ok(has_errors)
     }
 
+    /// Returns whether any of the printed diagnostics is an error.
     pub(crate) async fn print_schema_errors(
         &self,
         file: &SimpleFile<&str, &str>,
         errors: &[NodeValidationError],
-    ) -> Result<(), anyhow::Error> {
+        filter: &DiagnosticFilter,
+    ) -> Result<bool, anyhow::Error> {
         let config = codespan_reporting::term::Config::default();
 
         let mut out_diag = Vec::<u8>::new();
+        let mut has_errors = false;
         for err in errors {
-            let msg = err.error.to_string();
+            let msg = self.catalog.format(&err.message());
             for text_range in err.node.text_ranges() {
-                let diag = Diagnostic::error()
-                    .with_message(err.error.to_string())
+                let severity =
+                    match filter.severity(NodeValidationError::CODE, text_range, Severity::Error) {
+                        Some(s) => s,
+                        None => continue,
+                    };
+                has_errors |= severity == Severity::Error;
+
+                let diag = Diagnostic::new(diagnostic_severity(severity))
+                    .with_code(NodeValidationError::CODE)
+                    .with_message(&msg)
                     .with_labels(Vec::from([
                         Label::primary((), std_range(text_range)).with_message(&msg)
                     ]));
@@ -141,10 +173,213 @@ impl<E: Environment> Taplo<E> {
         stderr.write_all(&out_diag).await?;
         stderr.flush().await?;
 
+        Ok(has_errors)
+    }
+
+    /// Returns whether any of the printed diagnostics is an error.
+    pub(crate) async fn print_deprecations(
+        &self,
+        file: &SimpleFile<&str, &str>,
+        deprecations: &[Deprecation],
+        filter: &DiagnosticFilter,
+    ) -> Result<bool, anyhow::Error> {
+        let config = codespan_reporting::term::Config::default();
+
+        let mut out_diag = Vec::<u8>::new();
+        let mut has_errors = false;
+        for deprecation in deprecations {
+            let key = match deprecation.key() {
+                Some(k) => k,
+                None => continue,
+            };
+
+            for text_range in key.text_ranges() {
+                let severity =
+                    match filter.severity(Deprecation::CODE, text_range, Severity::Warning) {
+                        Some(s) => s,
+                        None => continue,
+                    };
+                has_errors |= severity == Severity::Error;
+
+                let diag = Diagnostic::new(diagnostic_severity(severity))
+                    .with_code(Deprecation::CODE)
+                    .with_message(self.catalog.format(&deprecation.message()))
+                    .with_labels(Vec::from([Label::primary((), std_range(text_range))
+                        .with_message(
+                            self.catalog.format(&Message::new("deprecated-key-label")),
+                        )]));
+
+                if self.colors {
+                    term::emit(&mut Ansi::new(&mut out_diag), &config, file, &diag)?;
+                } else {
+                    term::emit(&mut NoColor::new(&mut out_diag), &config, file, &diag)?;
+                };
+            }
+        }
+        let mut stderr = self.env.stderr();
+        stderr.write_all(&out_diag).await?;
+        stderr.flush().await?;
+
+        Ok(has_errors)
+    }
+
+    pub(crate) async fn print_merge_conflicts(
+        &self,
+        path: &str,
+        ours: &str,
+        theirs: &str,
+        conflicts: &[Conflict],
+    ) -> Result<(), anyhow::Error> {
+        let config = codespan_reporting::term::Config::default();
+
+        let mut files = SimpleFiles::new();
+        let ours_id = files.add(
+            self.catalog
+                .format(&Message::new("merge-ours-file").with_arg("path", path)),
+            ours,
+        );
+        let theirs_id = files.add(
+            self.catalog
+                .format(&Message::new("merge-theirs-file").with_arg("path", path)),
+            theirs,
+        );
+
+        let mut out_diag = Vec::<u8>::new();
+        for conflict in conflicts {
+            let mut labels = Vec::new();
+            let mut notes = Vec::new();
+
+            match conflict.ours_range() {
+                Some(range) => labels.push(
+                    Label::primary(ours_id, std_range(range))
+                        .with_message(self.catalog.format(&Message::new("merge-ours"))),
+                ),
+                None => notes.push(self.catalog.format(&Message::new("merge-removed-ours"))),
+            }
+
+            match conflict.theirs_range() {
+                Some(range) => labels.push(
+                    Label::secondary(theirs_id, std_range(range))
+                        .with_message(self.catalog.format(&Message::new("merge-theirs"))),
+                ),
+                None => notes.push(self.catalog.format(&Message::new("merge-removed-theirs"))),
+            }
+
+            let diag = Diagnostic::error()
+                .with_message(
+                    self.catalog
+                        .format(&Message::new("merge-conflict").with_arg("keys", &conflict.keys)),
+                )
+                .with_labels(labels)
+                .with_notes(notes);
+
+            if self.colors {
+                term::emit(&mut Ansi::new(&mut out_diag), &config, &files, &diag)?;
+            } else {
+                term::emit(&mut NoColor::new(&mut out_diag), &config, &files, &diag)?;
+            };
+        }
+        let mut stderr = self.env.stderr();
+        stderr.write_all(&out_diag).await?;
+        stderr.flush().await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn print_changes(
+        &self,
+        old_name: &str,
+        new_name: &str,
+        changes: impl Iterator<Item = Change>,
+    ) -> Result<(), anyhow::Error> {
+        let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+
+        for change in changes {
+            let mut lines = Vec::new();
+            match &change {
+                Change::Added { keys, node } => entry_lines("+", keys, node, &mut lines),
+                Change::Removed { keys, node } => entry_lines("-", keys, node, &mut lines),
+                Change::Modified { keys, old, new } if is_container(old) || is_container(new) => {
+                    entry_lines("-", keys, old, &mut lines);
+                    entry_lines("+", keys, new, &mut lines);
+                }
+                Change::Modified { keys, old, new } => lines.push(format!(
+                    "~ {}",
+                    self.catalog.format(
+                        &Message::new("diff-modified")
+                            .with_arg("keys", keys)
+                            .with_arg("old", old.to_toml(true, false))
+                            .with_arg("new", new.to_toml(true, false))
+                    )
+                )),
+            };
+
+            for line in lines {
+                if self.colors {
+                    out += &paint_change(line);
+                } else {
+                    out += &line;
+                }
+                out += "\n";
+            }
+        }
+
+        let mut stdout = self.env.stdout();
+        stdout.write_all(out.as_bytes()).await?;
+        stdout.flush().await?;
+
         Ok(())
     }
 }
 
+/// One line for each entry of the node, tables and arrays of tables
+/// are expanded instead of printed inline.
+fn entry_lines(prefix: &str, keys: &Keys, node: &Node, lines: &mut Vec<String>) {
+    match node {
+        Node::Table(table) if is_container(node) => {
+            let entries = table.entries().read();
+            if entries.is_empty() {
+                lines.push(format!("{prefix} {keys} = {{}}"));
+            }
+            for (key, entry) in entries.iter() {
+                entry_lines(prefix, &keys.join(key.clone()), entry, lines);
+            }
+        }
+        Node::Array(array) if is_container(node) => {
+            for (idx, item) in array.items().read().iter().enumerate() {
+                entry_lines(prefix, &keys.join(idx), item, lines);
+            }
+        }
+        _ => lines.push(format!("{prefix} {keys} = {}", node.to_toml(true, false))),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn paint_change(line: String) -> String {
+    use ansi_term::Colour::{Green, Red, Yellow};
+
+    match line.as_bytes().first() {
+        Some(b'+') => Green.paint(line).to_string(),
+        Some(b'-') => Red.paint(line).to_string(),
+        _ => Yellow.paint(line).to_string(),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn paint_change(line: String) -> String {
+    line
+}
+
+fn diagnostic_severity(severity: Severity) -> diagnostic::Severity {
+    match severity {
+        Severity::Error => diagnostic::Severity::Error,
+        Severity::Warning => diagnostic::Severity::Warning,
+        Severity::Information => diagnostic::Severity::Note,
+        Severity::Hint => diagnostic::Severity::Help,
+        Severity::Off => unreachable!("diagnostics that are off are not reported"),
+    }
+}
+
 fn std_range(range: TextRange) -> Range<usize> {
     let start: usize = u32::from(range.start()) as _;
     let end: usize = u32::from(range.end()) as _;
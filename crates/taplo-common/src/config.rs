@@ -7,7 +7,11 @@ use anyhow::Context;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use taplo::formatter;
+use taplo::{
+    diagnostics::{DiagnosticFilter, Severity},
+    formatter,
+    parser::Directive,
+};
 use url::Url;
 
 use crate::{
@@ -126,6 +130,30 @@ impl Config {
         }
     }
 
+    /// The filter for the diagnostics of a document with the given
+    /// `#:taplo` comments and the configured severities.
+    ///
+    /// Severities of rules that match the path take precedence
+    /// over the global ones, rules with `keys` are ignored.
+    #[must_use]
+    pub fn diagnostic_filter(&self, path: &Path, directives: &[Directive]) -> DiagnosticFilter {
+        let mut filter = DiagnosticFilter::new(directives);
+
+        if let Some(severities) = &self.global_options.severity {
+            filter = filter.with_severities(severities.clone());
+        }
+
+        for rule in self.rules_for(path) {
+            if rule.keys.is_none() {
+                if let Some(severities) = &rule.options.severity {
+                    filter = filter.with_severities(severities.clone());
+                }
+            }
+        }
+
+        filter
+    }
+
     pub fn format_scopes<'s>(
         &'s self,
         path: &'s Path,
@@ -236,6 +264,10 @@ pub struct Options {
     pub schema: Option<SchemaOptions>,
     /// Formatting options.
     pub formatting: Option<formatter::OptionsIncomplete>,
+    /// Severities of diagnostics by their codes, e.g. `conflicting-keys = "warning"`.
+    ///
+    /// Diagnostics can be disabled with `"off"`.
+    pub severity: Option<HashMap<String, Severity>>,
 }
 
 impl Options {
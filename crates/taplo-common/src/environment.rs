@@ -1,8 +1,12 @@
+use anyhow::anyhow;
 use async_trait::async_trait;
 use futures::Future;
 use std::path::{Path, PathBuf};
 use time::OffsetDateTime;
-use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc::UnboundedReceiver,
+};
 use url::Url;
 
 use crate::util::Normalize;
@@ -54,6 +58,16 @@ pub trait Environment: Clone + Send + Sync + 'static {
 
     async fn find_config_file(&self, from: &Path) -> Option<PathBuf>;
 
+    /// Watch the directories recursively, the paths of files
+    /// that are created or modified are sent to the returned receiver.
+    ///
+    /// Watching stops after the receiver is dropped.
+    fn watch(&self, _paths: &[PathBuf]) -> Result<UnboundedReceiver<PathBuf>, anyhow::Error> {
+        Err(anyhow!(
+            "watching files is not supported in this environment"
+        ))
+    }
+
     /// Same as [`Self::glob_files`], but the returned paths are
     /// [normalized](Normalize:normalize) in addition.
     fn glob_files_normalized(&self, glob: &str) -> Result<Vec<PathBuf>, anyhow::Error> {
@@ -4,7 +4,9 @@ use crate::config::CONFIG_FILE_NAMES;
 
 use super::Environment;
 use async_trait::async_trait;
+use notify::{EventKind, RecursiveMode, Watcher};
 use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 #[derive(Clone)]
 pub struct NativeEnvironment {
@@ -129,4 +131,46 @@ impl Environment for NativeEnvironment {
             }
         }
     }
+
+    fn watch(
+        &self,
+        paths: &[std::path::PathBuf],
+    ) -> Result<UnboundedReceiver<std::path::PathBuf>, anyhow::Error> {
+        let (events_tx, events_rx) = std::sync::mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(events_tx)?;
+        for path in paths {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        }
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        // The thread owns the watcher, it stops with the
+        // first event after the receiver is dropped.
+        std::thread::spawn(move || {
+            let _watcher = watcher;
+
+            for event in events_rx {
+                let event: notify::Event = match event {
+                    Ok(event) => event,
+                    Err(error) => {
+                        tracing::warn!(%error, "failed to watch files");
+                        continue;
+                    }
+                };
+
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    continue;
+                }
+
+                for path in event.paths {
+                    if tx.send(path).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
 }
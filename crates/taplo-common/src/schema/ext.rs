@@ -13,6 +13,19 @@ pub struct TaploSchemaExt {
     pub init_keys: Option<Vec<String>>,
     #[serde(default)]
     pub plugins: Vec<String>,
+    pub deprecated: Option<ExtDeprecated>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtDeprecated {
+    /// Additional information shown with the warning.
+    pub message: Option<String>,
+    /// The key that should be used instead.
+    ///
+    /// It is relative to the table that contains the deprecated key,
+    /// and can be a dotted key for moving the value into a nested table.
+    pub replacement: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -45,3 +58,32 @@ pub fn schema_ext_of(schema: &Value) -> Option<TaploSchemaExt> {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{schema_ext_of, ExtDeprecated};
+    use serde_json::json;
+
+    #[test]
+    fn deprecated() {
+        let ext = schema_ext_of(&json!({
+            "x-taplo": { "deprecated": { "message": "note", "replacement": "a.b" } }
+        }))
+        .unwrap();
+        assert_eq!(
+            ext.deprecated,
+            Some(ExtDeprecated {
+                message: Some("note".into()),
+                replacement: Some("a.b".into()),
+            })
+        );
+
+        let ext = schema_ext_of(&json!({ "x-taplo": { "deprecated": {} } })).unwrap();
+        assert_eq!(ext.deprecated, Some(ExtDeprecated::default()));
+
+        let ext = schema_ext_of(&json!({ "x-taplo": { "hidden": true } })).unwrap();
+        assert_eq!(ext.deprecated, None);
+
+        assert!(schema_ext_of(&json!({ "x-taplo": { "deprecated": true } })).is_none());
+    }
+}
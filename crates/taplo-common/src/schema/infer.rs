@@ -0,0 +1,323 @@
+//! Generating JSON schemas from example documents.
+
+use serde_json::{json, Map, Value};
+use taplo::dom::{node::DateTimeValue, Node};
+
+/// Generate a JSON schema from an example TOML document.
+///
+/// All keys that appear in the example are required,
+/// items of arrays are merged into a single schema, in which keys that
+/// are missing from some of the tables are optional, and values of
+/// different types are allowed with `anyOf`.
+///
+/// The result is only meant to be a starting point for a hand-written schema.
+#[must_use]
+pub fn infer(root: &Node) -> Value {
+    let mut schema = Map::new();
+    schema.insert(
+        "$schema".into(),
+        "http://json-schema.org/draft-07/schema#".into(),
+    );
+
+    if let Value::Object(inferred) = Shape::of(root).into_schema() {
+        schema.extend(inferred);
+    }
+
+    Value::Object(schema)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// No information about the value, e.g. items of an empty array.
+    Any,
+    Bool,
+    Integer,
+    Float,
+    Str {
+        format: Option<&'static str>,
+    },
+    Array(Box<Shape>),
+    Table {
+        properties: Vec<(String, Shape)>,
+        required: Vec<String>,
+    },
+    AnyOf(Vec<Shape>),
+}
+
+impl Shape {
+    fn of(node: &Node) -> Self {
+        match node {
+            Node::Table(table) => {
+                let entries = table.entries().read();
+                let properties: Vec<(String, Shape)> = entries
+                    .iter()
+                    .map(|(key, node)| (key.value().to_string(), Shape::of(node)))
+                    .collect();
+                let required = properties.iter().map(|(key, _)| key.clone()).collect();
+
+                Shape::Table {
+                    properties,
+                    required,
+                }
+            }
+            Node::Array(array) => Shape::Array(Box::new(
+                array
+                    .items()
+                    .read()
+                    .iter()
+                    .map(Shape::of)
+                    .reduce(Shape::merge)
+                    .unwrap_or(Shape::Any),
+            )),
+            Node::Bool(_) => Shape::Bool,
+            Node::Str(_) => Shape::Str { format: None },
+            Node::Integer(_) => Shape::Integer,
+            Node::Float(_) => Shape::Float,
+            Node::Date(date) => Shape::Str {
+                format: match date.value() {
+                    DateTimeValue::OffsetDateTime(_) => Some("date-time"),
+                    DateTimeValue::Date(_) => Some("date"),
+                    // There are no standard formats for these.
+                    DateTimeValue::LocalDateTime(_) | DateTimeValue::Time(_) => None,
+                },
+            },
+            Node::Unknown(_) | Node::Invalid(_) => Shape::Any,
+        }
+    }
+
+    /// A shape that accepts the values of both shapes.
+    fn merge(self, other: Shape) -> Shape {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Shape::Any, other) | (other, Shape::Any) => other,
+            (Shape::Integer | Shape::Float, Shape::Integer | Shape::Float) => Shape::Float,
+            (Shape::Str { .. }, Shape::Str { .. }) => Shape::Str { format: None },
+            (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(a.merge(*b))),
+            (
+                Shape::Table {
+                    properties: mut a_properties,
+                    required: a_required,
+                },
+                Shape::Table {
+                    properties: b_properties,
+                    required: b_required,
+                },
+            ) => {
+                for (key, b_shape) in b_properties {
+                    match a_properties.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, a_shape)) => {
+                            *a_shape = std::mem::replace(a_shape, Shape::Any).merge(b_shape);
+                        }
+                        None => a_properties.push((key, b_shape)),
+                    }
+                }
+
+                Shape::Table {
+                    properties: a_properties,
+                    required: a_required
+                        .into_iter()
+                        .filter(|key| b_required.contains(key))
+                        .collect(),
+                }
+            }
+            (Shape::AnyOf(mut shapes), other) | (other, Shape::AnyOf(mut shapes)) => {
+                let others = match other {
+                    Shape::AnyOf(others) => others,
+                    other => vec![other],
+                };
+
+                for other in others {
+                    match shapes.iter_mut().find(|s| s.is_compatible(&other)) {
+                        Some(shape) => *shape = std::mem::replace(shape, Shape::Any).merge(other),
+                        None => shapes.push(other),
+                    }
+                }
+
+                Shape::AnyOf(shapes)
+            }
+            (a, b) => Shape::AnyOf(vec![a, b]),
+        }
+    }
+
+    /// Whether the shapes can be merged without `anyOf`.
+    fn is_compatible(&self, other: &Shape) -> bool {
+        matches!(
+            (self, other),
+            (Shape::Bool, Shape::Bool)
+                | (Shape::Integer | Shape::Float, Shape::Integer | Shape::Float)
+                | (Shape::Str { .. }, Shape::Str { .. })
+                | (Shape::Array(_), Shape::Array(_))
+                | (Shape::Table { .. }, Shape::Table { .. })
+        )
+    }
+
+    fn into_schema(self) -> Value {
+        match self {
+            Shape::Any => json!({}),
+            Shape::Bool => json!({ "type": "boolean" }),
+            Shape::Integer => json!({ "type": "integer" }),
+            Shape::Float => json!({ "type": "number" }),
+            Shape::Str { format: None } => json!({ "type": "string" }),
+            Shape::Str {
+                format: Some(format),
+            } => json!({ "type": "string", "format": format }),
+            Shape::Array(items) => match *items {
+                Shape::Any => json!({ "type": "array" }),
+                items => json!({ "type": "array", "items": items.into_schema() }),
+            },
+            Shape::Table {
+                properties,
+                required,
+            } => {
+                let mut schema = Map::new();
+                schema.insert("type".into(), "object".into());
+                schema.insert(
+                    "properties".into(),
+                    Value::Object(
+                        properties
+                            .into_iter()
+                            .map(|(key, shape)| (key, shape.into_schema()))
+                            .collect(),
+                    ),
+                );
+
+                if !required.is_empty() {
+                    schema.insert("required".into(), required.into());
+                }
+
+                Value::Object(schema)
+            }
+            Shape::AnyOf(shapes) => json!({
+                "anyOf": shapes.into_iter().map(Shape::into_schema).collect::<Vec<_>>()
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::infer;
+    use serde_json::{json, Value};
+    use taplo::parser::parse;
+
+    fn properties(src: &str) -> Value {
+        infer(&parse(src).into_dom())["properties"].clone()
+    }
+
+    #[test]
+    fn root() {
+        let schema =
+            infer(&parse("a = true\nb = 1979-05-27T07:32:00Z\nc = 1979-05-27\n").into_dom());
+
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "a": { "type": "boolean" },
+                    "b": { "type": "string", "format": "date-time" },
+                    "c": { "type": "string", "format": "date" },
+                },
+                "required": ["a", "b", "c"],
+            })
+        );
+    }
+
+    #[test]
+    fn arrays() {
+        let properties = properties(
+            r#"
+empty = []
+numbers = [1, 2.5]
+nested = [[1], [], [2.0]]
+"#,
+        );
+
+        assert_eq!(properties["empty"], json!({ "type": "array" }));
+        assert_eq!(
+            properties["numbers"],
+            json!({ "type": "array", "items": { "type": "number" } })
+        );
+        assert_eq!(
+            properties["nested"],
+            json!({
+                "type": "array",
+                "items": { "type": "array", "items": { "type": "number" } },
+            })
+        );
+    }
+
+    #[test]
+    fn optional_keys() {
+        let properties = properties(
+            r#"
+[[bin]]
+name = "a"
+path = "src/a.rs"
+
+[[bin]]
+name = "b"
+test = false
+"#,
+        );
+
+        assert_eq!(
+            properties["bin"],
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "path": { "type": "string" },
+                        "test": { "type": "boolean" },
+                    },
+                    "required": ["name"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn mixed_types() {
+        let properties = properties(
+            r#"
+mixed = [1, "a", true, 2.5, "b"]
+tables = [{ a = 1 }, "s", { b = 1979-05-27 }, { a = 2.5 }]
+"#,
+        );
+
+        assert_eq!(
+            properties["mixed"],
+            json!({
+                "type": "array",
+                "items": {
+                    "anyOf": [
+                        { "type": "number" },
+                        { "type": "string" },
+                        { "type": "boolean" },
+                    ],
+                },
+            })
+        );
+        assert_eq!(
+            properties["tables"],
+            json!({
+                "type": "array",
+                "items": {
+                    "anyOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "a": { "type": "number" },
+                                "b": { "type": "string", "format": "date" },
+                            },
+                        },
+                        { "type": "string" },
+                    ],
+                },
+            })
+        );
+    }
+}
@@ -1,16 +1,31 @@
-use self::{associations::SchemaAssociations, builtins::builtin_schema, cache::Cache};
-use crate::{environment::Environment, util::ArcHashValue, LruCache};
+use self::{
+    associations::SchemaAssociations, builtins::builtin_schema, cache::Cache, ext::schema_ext_of,
+};
+use crate::{environment::Environment, util::ArcHashValue, HashSet, LruCache};
 use anyhow::{anyhow, Context};
 use async_recursion::async_recursion;
 use futures::{stream::FuturesUnordered, StreamExt};
 use itertools::Itertools;
 use json_value_merge::Merge;
-use jsonschema::{error::ValidationErrorKind, JSONSchema, SchemaResolver, ValidationError};
+use jsonschema::{
+    error::{TypeKind, ValidationErrorKind},
+    primitive_type::PrimitiveType,
+    JSONSchema, SchemaResolver, ValidationError,
+};
 use parking_lot::Mutex;
 use regex::Regex;
 use serde_json::Value;
 use std::{borrow::Cow, sync::Arc};
-use taplo::dom::{self, node::Key, KeyOrIndex, Keys};
+use tap::TapFallible;
+use taplo::{
+    dom::{
+        self,
+        coerce::{coerce, ScalarKind},
+        node::{Key, TableKind},
+        KeyOrIndex, Keys,
+    },
+    messages::Message,
+};
 use thiserror::Error;
 use tokio::sync::Semaphore;
 use url::Url;
@@ -19,6 +34,9 @@ pub mod associations;
 pub mod cache;
 pub mod ext;
 
+mod infer;
+pub use infer::infer;
+
 pub mod builtins {
     use reqwest::Url;
     use serde_json::Value;
@@ -48,6 +66,7 @@ pub struct Schemas<E: Environment> {
     concurrent_requests: Arc<Semaphore>,
     http: reqwest::Client,
     validators: Arc<Mutex<LruCache<Url, Arc<JSONSchema>>>>,
+    branch_validators: Arc<Mutex<LruCache<(Url, ArcHashValue), Arc<JSONSchema>>>>,
     cache: Cache<E>,
 }
 
@@ -65,6 +84,10 @@ impl<E: Environment> Schemas<E> {
                 3,
                 ahash::RandomState::new(),
             ))),
+            branch_validators: Arc::new(Mutex::new(LruCache::with_hasher(
+                64,
+                ahash::RandomState::new(),
+            ))),
         }
     }
 
@@ -120,6 +143,251 @@ impl<E: Environment> Schemas<E> {
         self.validate_impl(&validator, value).await
     }
 
+    /// Collect all keys in the document that are marked as deprecated
+    /// in the schema, either with `deprecated` or with the `x-taplo` extension.
+    ///
+    /// The document and the schema are walked together in a single pass.
+    #[tracing::instrument(skip_all, fields(%schema_url))]
+    pub async fn deprecations(
+        &self,
+        schema_url: &Url,
+        root: &dom::Node,
+    ) -> Result<Vec<Deprecation>, anyhow::Error> {
+        let schema = self.load_schema(schema_url).await?;
+
+        let mut deprecations = Vec::new();
+        self.collect_deprecations(
+            schema_url,
+            vec![schema],
+            root,
+            Keys::empty(),
+            &mut deprecations,
+        )
+        .await?;
+
+        Ok(deprecations)
+    }
+
+    #[async_recursion(?Send)]
+    async fn collect_deprecations(
+        &self,
+        root_url: &Url,
+        schemas: Vec<Arc<Value>>,
+        node: &dom::Node,
+        keys: Keys,
+        deprecations: &mut Vec<Deprecation>,
+    ) -> Result<(), anyhow::Error> {
+        // The node is only serialized if a `oneOf` or `anyOf` branch has to be checked.
+        let mut value = None;
+        let mut visited = HashSet::default();
+        let mut resolved = Vec::with_capacity(schemas.len());
+        for schema in schemas {
+            self.resolve_subschemas(
+                root_url,
+                schema,
+                node,
+                &mut value,
+                false,
+                &mut visited,
+                &mut resolved,
+            )
+            .await?;
+        }
+
+        if resolved.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(KeyOrIndex::Key(_)) = keys.iter().last() {
+            if let Some(deprecation) = resolved
+                .iter()
+                .find_map(|schema| Deprecation::from_schema(&keys, schema))
+            {
+                deprecations.push(deprecation);
+            }
+        }
+
+        match node {
+            dom::Node::Table(table) => {
+                let entries: Vec<_> = table
+                    .entries()
+                    .read()
+                    .iter()
+                    .map(|(key, node)| (key.clone(), node.clone()))
+                    .collect();
+
+                for (key, child) in entries {
+                    let schemas = resolved
+                        .iter()
+                        .flat_map(|schema| property_schemas(schema, key.value()))
+                        .collect();
+
+                    self.collect_deprecations(
+                        root_url,
+                        schemas,
+                        &child,
+                        keys.join(key),
+                        deprecations,
+                    )
+                    .await?;
+                }
+            }
+            dom::Node::Array(array) => {
+                let items = array.items().read().clone();
+
+                for (idx, item) in items.iter().enumerate() {
+                    let schemas = resolved
+                        .iter()
+                        .filter_map(|schema| match &schema["items"] {
+                            Value::Array(items) => items.get(idx),
+                            items => Some(items),
+                        })
+                        .map(|schema| Arc::new(schema.clone()))
+                        .collect();
+
+                    self.collect_deprecations(
+                        root_url,
+                        schemas,
+                        item,
+                        keys.join(idx),
+                        deprecations,
+                    )
+                    .await?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the references of a schema and collect the schemas it is composed of.
+    ///
+    /// Only the `oneOf` and `anyOf` branches that the value is valid against are collected,
+    /// references that were already visited are skipped.
+    /// The node is serialized into `value` the first time a branch is checked.
+    #[allow(clippy::too_many_arguments)]
+    #[async_recursion(?Send)]
+    async fn resolve_subschemas(
+        &self,
+        root_url: &Url,
+        schema: Arc<Value>,
+        node: &dom::Node,
+        value: &mut Option<Value>,
+        branch: bool,
+        visited: &mut HashSet<Url>,
+        resolved: &mut Vec<Arc<Value>>,
+    ) -> Result<(), anyhow::Error> {
+        if !schema.is_object() {
+            return Ok(());
+        }
+
+        if let Some(r) = schema.schema_ref() {
+            let url = reference_url(root_url, r)
+                .ok_or_else(|| anyhow!("could not determine schema URL"))?;
+
+            if !visited.insert(url.clone()) {
+                return Ok(());
+            }
+
+            let schema = self.resolve_schema(url).await?;
+            return self
+                .resolve_subschemas(root_url, schema, node, value, branch, visited, resolved)
+                .await;
+        }
+
+        if branch {
+            if value.is_none() {
+                *value = Some(serde_json::to_value(node)?);
+            }
+
+            if let Some(value) = value {
+                if !self.is_valid_branch(root_url, &schema, value).await {
+                    return Ok(());
+                }
+            }
+        }
+
+        if let Some(subschemas) = schema["allOf"].as_array() {
+            for subschema in subschemas {
+                self.resolve_subschemas(
+                    root_url,
+                    Arc::new(subschema.clone()),
+                    node,
+                    value,
+                    false,
+                    visited,
+                    resolved,
+                )
+                .await?;
+            }
+        }
+
+        for composition in ["oneOf", "anyOf"] {
+            if let Some(subschemas) = schema[composition].as_array() {
+                for subschema in subschemas {
+                    self.resolve_subschemas(
+                        root_url,
+                        Arc::new(subschema.clone()),
+                        node,
+                        value,
+                        true,
+                        visited,
+                        resolved,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        resolved.push(schema);
+
+        Ok(())
+    }
+
+    /// Whether the value is valid against a `oneOf` or `anyOf` branch.
+    ///
+    /// The definitions of the root schema are copied into the branch,
+    /// so that local references can be resolved.
+    /// Compiled branches are cached by the root schema URL and the branch schema.
+    async fn is_valid_branch(&self, root_url: &Url, schema: &Arc<Value>, value: &Value) -> bool {
+        if self.cache().lru_expired() {
+            self.branch_validators.lock().clear();
+        }
+
+        let cache_key = (root_url.clone(), ArcHashValue(schema.clone()));
+
+        let validator = self.branch_validators.lock().get(&cache_key).cloned();
+        if let Some(validator) = validator {
+            return validator.is_valid(value);
+        }
+
+        let mut schema = (**schema).clone();
+
+        if let (Ok(root), Some(obj)) = (self.load_schema(root_url).await, schema.as_object_mut()) {
+            for definitions in ["definitions", "$defs"] {
+                if !root[definitions].is_null() {
+                    obj.entry(definitions)
+                        .or_insert_with(|| root[definitions].clone());
+                }
+            }
+        }
+
+        match self.create_validator(&schema) {
+            Ok(validator) => {
+                let valid = validator.is_valid(value);
+                self.branch_validators
+                    .lock()
+                    .put(cache_key, Arc::new(validator));
+                valid
+            }
+            Err(error) => {
+                tracing::debug!(%error, "invalid schema branch");
+                false
+            }
+        }
+    }
+
     async fn validate_impl(
         &self,
         validator: &JSONSchema,
@@ -628,6 +896,37 @@ impl<E: Environment> Schemas<E> {
     }
 }
 
+/// The schemas of the value of a key in an object with the given schema.
+fn property_schemas(schema: &Value, key: &str) -> Vec<Arc<Value>> {
+    let mut schemas = Vec::new();
+    let mut matched = false;
+
+    if let Some(prop_schema) = schema["properties"].get(key) {
+        matched = true;
+        if prop_schema.is_object() {
+            schemas.push(Arc::new(prop_schema.clone()));
+        }
+    }
+
+    if let Some(pattern_props) = schema["patternProperties"].as_object() {
+        for (pattern, pattern_schema) in pattern_props {
+            if matches!(Regex::new(pattern), Ok(re) if re.is_match(key)) {
+                matched = true;
+                if pattern_schema.is_object() {
+                    schemas.push(Arc::new(pattern_schema.clone()));
+                }
+            }
+        }
+    }
+
+    // Additional properties only apply to keys that no other property matched.
+    if !matched && schema["additionalProperties"].is_object() {
+        schemas.push(Arc::new(schema["additionalProperties"].clone()));
+    }
+
+    schemas
+}
+
 fn reference_url(root_url: &Url, reference: &str) -> Option<Url> {
     if !reference.starts_with('#') {
         return Url::parse(reference).ok();
@@ -682,6 +981,10 @@ pub struct NodeValidationError {
 }
 
 impl NodeValidationError {
+    /// The code of schema validation errors for
+    /// [filtering diagnostics](taplo::diagnostics).
+    pub const CODE: &'static str = "schema-validation";
+
     fn new(root: &dom::Node, error: ValidationError<'static>) -> Result<Self, anyhow::Error> {
         let mut keys = Keys::empty();
         let mut node = root.clone();
@@ -712,6 +1015,244 @@ impl NodeValidationError {
 
         Ok(Self { keys, node, error })
     }
+
+    /// The message of the error,
+    /// it can be formatted with a [`Catalog`](taplo::messages::Catalog) for translations.
+    #[must_use]
+    pub fn message(&self) -> Message {
+        let value = self.error.instance.to_string();
+
+        match &self.error.kind {
+            ValidationErrorKind::Type { kind } => {
+                let types: Vec<PrimitiveType> = match kind {
+                    TypeKind::Single(ty) => vec![*ty],
+                    TypeKind::Multiple(types) => types.into_iter().collect(),
+                };
+
+                Message::new("schema-type")
+                    .with_arg("value", value)
+                    .with_arg(
+                        "types",
+                        types.iter().map(|ty| format!(r#""{ty}""#)).join(", "),
+                    )
+            }
+            ValidationErrorKind::Required { property } => {
+                Message::new("schema-required").with_arg("property", property)
+            }
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                Message::new("schema-additional-properties").with_arg(
+                    "properties",
+                    unexpected.iter().map(|p| format!(r#""{p}""#)).join(", "),
+                )
+            }
+            ValidationErrorKind::AdditionalItems { limit } => {
+                Message::new("schema-additional-items").with_arg("limit", limit)
+            }
+            ValidationErrorKind::Enum { options } => Message::new("schema-enum")
+                .with_arg("value", value)
+                .with_arg("options", options),
+            ValidationErrorKind::Constant { expected_value } => {
+                Message::new("schema-const").with_arg("expected", expected_value)
+            }
+            ValidationErrorKind::Format { format } => Message::new("schema-format")
+                .with_arg("value", value)
+                .with_arg("format", format),
+            ValidationErrorKind::Pattern { pattern } => Message::new("schema-pattern")
+                .with_arg("value", value)
+                .with_arg("pattern", pattern),
+            ValidationErrorKind::Minimum { limit } => Message::new("schema-minimum")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::Maximum { limit } => Message::new("schema-maximum")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::ExclusiveMinimum { limit } => {
+                Message::new("schema-exclusive-minimum")
+                    .with_arg("value", value)
+                    .with_arg("limit", limit)
+            }
+            ValidationErrorKind::ExclusiveMaximum { limit } => {
+                Message::new("schema-exclusive-maximum")
+                    .with_arg("value", value)
+                    .with_arg("limit", limit)
+            }
+            ValidationErrorKind::MinLength { limit } => Message::new("schema-min-length")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MaxLength { limit } => Message::new("schema-max-length")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MinItems { limit } => Message::new("schema-min-items")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MaxItems { limit } => Message::new("schema-max-items")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MinProperties { limit } => Message::new("schema-min-properties")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MaxProperties { limit } => Message::new("schema-max-properties")
+                .with_arg("value", value)
+                .with_arg("limit", limit),
+            ValidationErrorKind::MultipleOf { multiple_of } => Message::new("schema-multiple-of")
+                .with_arg("value", value)
+                .with_arg("multiple", multiple_of),
+            ValidationErrorKind::UniqueItems => {
+                Message::new("schema-unique-items").with_arg("value", value)
+            }
+            ValidationErrorKind::AnyOf | ValidationErrorKind::OneOfNotValid => {
+                Message::new("schema-any-of").with_arg("value", value)
+            }
+            ValidationErrorKind::OneOfMultipleValid => {
+                Message::new("schema-one-of-multiple").with_arg("value", value)
+            }
+            ValidationErrorKind::Contains => {
+                Message::new("schema-contains").with_arg("value", value)
+            }
+            ValidationErrorKind::Not { .. } | ValidationErrorKind::FalseSchema => {
+                Message::new("schema-not").with_arg("value", value)
+            }
+            ValidationErrorKind::PropertyNames { error } => {
+                Message::new("schema-property-names").with_arg("error", error)
+            }
+            // Errors of the schema itself rather than the document.
+            _ => Message::new("schema-error").with_arg("error", &self.error),
+        }
+    }
+
+    /// A lossless conversion of the value that fixes a type error,
+    /// e.g. `"8080"` where an integer is expected.
+    ///
+    /// Returns the type and the TOML text of the converted value.
+    #[must_use]
+    pub fn coercion(&self) -> Option<(ScalarKind, String)> {
+        let types: Vec<PrimitiveType> = match &self.error.kind {
+            ValidationErrorKind::Type {
+                kind: TypeKind::Single(ty),
+            } => vec![*ty],
+            ValidationErrorKind::Type {
+                kind: TypeKind::Multiple(types),
+            } => types.into_iter().collect(),
+            _ => return None,
+        };
+
+        types
+            .into_iter()
+            .flat_map(|ty| match ty {
+                PrimitiveType::Boolean => &[ScalarKind::Bool][..],
+                PrimitiveType::Integer => &[ScalarKind::Integer][..],
+                PrimitiveType::Number => &[ScalarKind::Integer, ScalarKind::Float][..],
+                PrimitiveType::String => &[ScalarKind::Str][..],
+                _ => &[][..],
+            })
+            .find_map(|&kind| coerce(&self.node, kind).map(|text| (kind, text)))
+    }
+}
+
+/// A key that is deprecated according to the schema.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub keys: Keys,
+    pub message: Option<String>,
+    /// The key that should be used instead,
+    /// relative to the table that contains the deprecated key.
+    pub replacement: Option<Keys>,
+}
+
+impl Deprecation {
+    /// The code of deprecation warnings for
+    /// [filtering diagnostics](taplo::diagnostics).
+    pub const CODE: &'static str = "deprecated-key";
+
+    fn from_schema(keys: &Keys, schema: &Value) -> Option<Self> {
+        let (message, replacement) = match schema_ext_of(schema).and_then(|ext| ext.deprecated) {
+            Some(deprecated) => (deprecated.message, deprecated.replacement),
+            None if schema["deprecated"] == true => (None, None),
+            None => return None,
+        };
+
+        let replacement = replacement.and_then(|replacement| {
+            replacement
+                .parse::<Keys>()
+                .tap_err(|error| tracing::warn!(%error, %replacement, "invalid replacement key"))
+                .ok()
+        });
+
+        Some(Self {
+            keys: keys.clone(),
+            message,
+            replacement,
+        })
+    }
+
+    /// The full path of the replacement key, if any.
+    #[must_use]
+    pub fn replacement_keys(&self) -> Option<Keys> {
+        let replacement = self.replacement.as_ref()?;
+        Some(self.keys.skip_right(1).extend(replacement.clone()))
+    }
+
+    /// Whether the key can be replaced in the document without conflicts,
+    /// i.e. the replacement key is not defined yet and its parent keys
+    /// are not defined as anything other than dotted keys.
+    #[must_use]
+    pub fn can_replace(&self, root: &dom::Node) -> bool {
+        let target = match self.replacement_keys() {
+            Some(target) => target,
+            None => return false,
+        };
+
+        let parent_len = self.keys.len().saturating_sub(1);
+
+        for len in parent_len + 1..=target.len() {
+            match root.path(&target.skip_right(target.len() - len)) {
+                None => return true,
+                Some(dom::Node::Table(table))
+                    if len < target.len() && table.kind() == TableKind::Pseudo => {}
+                Some(_) => return false,
+            }
+        }
+
+        false
+    }
+
+    /// The last key of the path, the one that is deprecated.
+    #[must_use]
+    pub fn key(&self) -> Option<&Key> {
+        match self.keys.iter().last() {
+            Some(KeyOrIndex::Key(k)) => Some(k),
+            _ => None,
+        }
+    }
+
+    /// The warning message,
+    /// it can be formatted with a [`Catalog`](taplo::messages::Catalog) for translations.
+    #[must_use]
+    pub fn message(&self) -> Message {
+        let message = match (&self.replacement, &self.message) {
+            (Some(_), Some(_)) => Message::new("deprecated-key-replacement-note"),
+            (Some(_), None) => Message::new("deprecated-key-replacement"),
+            (None, Some(_)) => Message::new("deprecated-key-note"),
+            (None, None) => Message::new("deprecated-key"),
+        }
+        .with_arg("key", self.keys.dotted());
+
+        let message = match self.replacement_keys() {
+            Some(replacement) => message.with_arg("replacement", replacement.dotted()),
+            None => message,
+        };
+
+        match &self.message {
+            Some(note) => message.with_arg("note", note),
+            None => message,
+        }
+    }
+}
+
+impl core::fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.message().fmt(f)
+    }
 }
 
 mod formats {
@@ -723,3 +1264,188 @@ mod formats {
         semver::VersionReq::parse(value).is_ok()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Schemas;
+    use crate::environment::native::NativeEnvironment;
+    use serde_json::json;
+    use std::sync::Arc;
+    use taplo::parser::parse;
+    use url::Url;
+
+    #[tokio::test]
+    async fn deprecations() {
+        let schemas = Schemas::new(NativeEnvironment::new(), reqwest::Client::new());
+        let url: Url = "https://example.com/schema.json".parse().unwrap();
+        schemas
+            .add_schema(
+                &url,
+                Arc::new(json!({
+                    "definitions": {
+                        "old": { "deprecated": true }
+                    },
+                    "properties": {
+                        "package": {
+                            "properties": {
+                                "authors": {
+                                    "x-taplo": {
+                                        "deprecated": {
+                                            "message": "Moved to the metadata.",
+                                            "replacement": "metadata.authors"
+                                        }
+                                    }
+                                },
+                                "old": { "$ref": "#/definitions/old" }
+                            }
+                        },
+                        "bin": {
+                            "items": {
+                                "properties": {
+                                    "path": {
+                                        "anyOf": [{
+                                            "type": "string",
+                                            "x-taplo": { "deprecated": { "replacement": "src" } }
+                                        }]
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })),
+            )
+            .await;
+
+        let dom = parse(
+            r#"
+[package]
+authors = ["a"]
+old = 1
+name = "x"
+
+[[bin]]
+path = "a.rs"
+
+[[bin]]
+src = "b.rs"
+path = "b.rs"
+"#,
+        )
+        .into_dom();
+
+        let deprecations = schemas.deprecations(&url, &dom).await.unwrap();
+
+        let keys: Vec<_> = deprecations.iter().map(|d| d.keys.dotted()).collect();
+        assert_eq!(
+            keys,
+            ["package.authors", "package.old", "bin.0.path", "bin.1.path"]
+        );
+
+        assert_eq!(
+            deprecations[0].message.as_deref(),
+            Some("Moved to the metadata.")
+        );
+        assert_eq!(
+            deprecations[0].replacement_keys().unwrap().dotted(),
+            "package.metadata.authors"
+        );
+        assert!(deprecations[0].can_replace(&dom));
+
+        assert!(deprecations[1].replacement.is_none());
+        assert!(!deprecations[1].can_replace(&dom));
+
+        assert!(deprecations[2].can_replace(&dom));
+        // `src` is already defined in the second table.
+        assert!(!deprecations[3].can_replace(&dom));
+    }
+
+    #[tokio::test]
+    async fn deprecations_in_compositions() {
+        let schemas = Schemas::new(NativeEnvironment::new(), reqwest::Client::new());
+        let url: Url = "https://example.com/schema.json".parse().unwrap();
+        schemas
+            .add_schema(
+                &url,
+                Arc::new(json!({
+                    "definitions": {
+                        "cycle": { "$ref": "#/definitions/cycle" },
+                        "string": { "type": "string" }
+                    },
+                    "allOf": [{ "required": ["name"] }],
+                    "properties": {
+                        "old": { "deprecated": true },
+                        "name": {
+                            "anyOf": [
+                                { "type": "integer", "deprecated": true },
+                                { "$ref": "#/definitions/string" }
+                            ]
+                        },
+                        "version": {
+                            "oneOf": [
+                                { "type": "integer" },
+                                {
+                                    "allOf": [{ "$ref": "#/definitions/string" }],
+                                    "deprecated": true
+                                }
+                            ]
+                        },
+                        "cycle": { "$ref": "#/definitions/cycle" }
+                    }
+                })),
+            )
+            .await;
+
+        let dom = parse(
+            r#"
+old = 1
+name = "x"
+version = "1"
+cycle = 1
+"#,
+        )
+        .into_dom();
+
+        let deprecations = schemas.deprecations(&url, &dom).await.unwrap();
+
+        let keys: Vec<_> = deprecations.iter().map(|d| d.keys.dotted()).collect();
+        assert_eq!(keys, ["old", "version"]);
+    }
+
+    #[tokio::test]
+    async fn deprecated_additional_properties() {
+        let schemas = Schemas::new(NativeEnvironment::new(), reqwest::Client::new());
+        let url: Url = "https://example.com/schema.json".parse().unwrap();
+        schemas
+            .add_schema(
+                &url,
+                Arc::new(json!({
+                    "properties": {
+                        "name": { "type": "string" },
+                        "flag": true
+                    },
+                    "patternProperties": {
+                        "^x-": { "type": "string" }
+                    },
+                    "additionalProperties": {
+                        "x-taplo": { "deprecated": { "replacement": "name" } }
+                    }
+                })),
+            )
+            .await;
+
+        let dom = parse(
+            r#"
+name = "x"
+flag = true
+x-extra = "y"
+unknown = 1
+"#,
+        )
+        .into_dom();
+
+        let deprecations = schemas.deprecations(&url, &dom).await.unwrap();
+
+        let keys: Vec<_> = deprecations.iter().map(|d| d.keys.dotted()).collect();
+        assert_eq!(keys, ["unknown"]);
+    }
+}
@@ -3,6 +3,7 @@ use lsp_types::Url;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::PathBuf;
+use taplo::messages::Catalog;
 use taplo_common::{
     config::Rule,
     schema::{associations::DEFAULT_CATALOGS, cache::DEFAULT_LRU_CACHE_EXPIRATION_TIME},
@@ -15,6 +16,9 @@ pub struct InitConfig {
     pub cache_path: Option<PathBuf>,
     #[serde(default = "default_configuration_section")]
     pub configuration_section: String,
+    /// Message templates by diagnostic code that replace the default English messages.
+    #[serde(default)]
+    pub message_catalog: Catalog,
 }
 
 impl Default for InitConfig {
@@ -22,6 +26,7 @@ impl Default for InitConfig {
         Self {
             cache_path: Default::default(),
             configuration_section: default_configuration_section(),
+            message_catalog: Catalog::default(),
         }
     }
 }
@@ -2,11 +2,42 @@ use crate::world::{DocumentState, WorkspaceState, World};
 use either::Either;
 use lsp_async_stub::{util::LspExt, Context, RequestWriter};
 use lsp_types::{
-    notification, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
-    PublishDiagnosticsParams, Url,
+    notification, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag,
+    Location, NumberOrString, PublishDiagnosticsParams, TextEdit, Url,
 };
-use taplo::dom::{KeyOrIndex, Node};
-use taplo_common::environment::Environment;
+use serde::{Deserialize, Serialize};
+use taplo::{
+    diagnostics::{self, DiagnosticFilter, Severity},
+    dom::{
+        rewrite::{PendingPatchKind, Rewrite},
+        KeyOrIndex, Node,
+    },
+    messages::{Catalog, Message},
+};
+use taplo_common::{
+    environment::Environment,
+    schema::{Deprecation, NodeValidationError},
+};
+
+/// A fix for a diagnostic, stored in the diagnostic's data
+/// and offered as a quick fix code action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuickFix {
+    pub(crate) title: String,
+    pub(crate) edits: Vec<TextEdit>,
+    /// Whether the fix is safe to apply without asking, e.g. on "auto-fix".
+    #[serde(default)]
+    pub(crate) preferred: bool,
+}
+
+impl QuickFix {
+    fn into_data(self) -> Option<serde_json::Value> {
+        serde_json::to_value(self)
+            .map_err(|error| tracing::error!(%error, "failed to serialize quick fix"))
+            .ok()
+    }
+}
 
 #[tracing::instrument(skip_all)]
 pub(crate) async fn publish_diagnostics<E: Environment>(
@@ -16,6 +47,9 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
 ) {
     let mut diags = Vec::new();
 
+    let init_config = context.init_config.load_full();
+    let catalog = &init_config.message_catalog;
+
     let workspaces = context.workspaces.read().await;
     let ws = match workspaces.get(&ws_url) {
         Some(d) => d,
@@ -29,7 +63,14 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
         None => return,
     };
 
-    collect_syntax_errors(doc, &mut diags);
+    let filter = match context.env.to_file_path_normalized(&document_url) {
+        Some(path) => ws
+            .taplo_config
+            .diagnostic_filter(&path, &doc.parse.directives),
+        None => DiagnosticFilter::new(&doc.parse.directives),
+    };
+
+    collect_syntax_errors(doc, catalog, &filter, &mut diags);
     drop(workspaces);
 
     context
@@ -41,7 +82,10 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
         .await
         .unwrap_or_else(|err| tracing::error!("{err}"));
 
-    if !diags.is_empty() {
+    if diags
+        .iter()
+        .any(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+    {
         return;
     }
 
@@ -60,7 +104,7 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
 
     let dom = doc.dom.clone();
 
-    collect_dom_errors(doc, &dom, &document_url, &mut diags);
+    collect_dom_errors(doc, &dom, &document_url, catalog, &filter, &mut diags);
     drop(workspaces);
 
     context
@@ -72,7 +116,10 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
         .await
         .unwrap_or_else(|err| tracing::error!("{err}"));
 
-    if !diags.is_empty() {
+    if diags
+        .iter()
+        .any(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+    {
         return;
     }
 
@@ -89,7 +136,7 @@ pub(crate) async fn publish_diagnostics<E: Environment>(
         None => return,
     };
 
-    collect_schema_errors(ws, doc, &dom, &document_url, &mut diags).await;
+    collect_schema_errors(ws, doc, &dom, &document_url, catalog, &filter, &mut diags).await;
     drop(workspaces);
 
     context
@@ -118,20 +165,26 @@ pub(crate) async fn clear_diagnostics<E: Environment>(
 }
 
 #[tracing::instrument(skip_all)]
-fn collect_syntax_errors(doc: &DocumentState, diags: &mut Vec<Diagnostic>) {
-    diags.extend(doc.parse.errors.iter().map(|e| {
+fn collect_syntax_errors(
+    doc: &DocumentState,
+    catalog: &Catalog,
+    filter: &DiagnosticFilter,
+    diags: &mut Vec<Diagnostic>,
+) {
+    diags.extend(doc.parse.errors.iter().filter_map(|e| {
+        let severity = filter.diagnostic_severity(&diagnostics::Diagnostic::from(e))?;
         let range = doc.mapper.range(e.range).unwrap_or_default().into_lsp();
-        Diagnostic {
+        Some(Diagnostic {
             range,
-            severity: Some(DiagnosticSeverity::ERROR),
-            code: None,
+            severity: Some(lsp_severity(severity)),
+            code: Some(NumberOrString::String(e.message.code.into())),
             code_description: None,
             source: Some("Even Better TOML".into()),
-            message: e.message.clone(),
+            message: catalog.format(&e.message),
             related_information: None,
             tags: None,
             data: None,
-        }
+        })
     }));
 }
 
@@ -140,10 +193,19 @@ fn collect_dom_errors(
     doc: &DocumentState,
     dom: &Node,
     document_url: &Url,
+    catalog: &Catalog,
+    filter: &DiagnosticFilter,
     diags: &mut Vec<Diagnostic>,
 ) {
     if let Err(errors) = dom.validate() {
         for error in errors {
+            // Hints of suppressed errors are not reported either.
+            let severity = match filter.error_severity(&error) {
+                Some(s) => lsp_severity(s),
+                None => continue,
+            };
+            let code = Some(NumberOrString::String(error.message().code.into()));
+
             match &error {
                 taplo::dom::Error::ConflictingKeys { key, other } => {
                     let range = doc
@@ -160,15 +222,16 @@ fn collect_dom_errors(
 
                     diags.push(Diagnostic {
                         range,
-                        severity: Some(DiagnosticSeverity::ERROR),
+                        severity: Some(severity),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range: other_range,
                             },
-                            message: "other key defined here".into(),
+                            message: catalog.format(&Message::new("other-key-defined-here")),
                         }])),
                         ..Default::default()
                     });
@@ -176,14 +239,15 @@ fn collect_dom_errors(
                     diags.push(Diagnostic {
                         range: other_range,
                         severity: Some(DiagnosticSeverity::HINT),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range,
                             },
-                            message: "other key defined here".into(),
+                            message: catalog.format(&Message::new("other-key-defined-here")),
                         }])),
                         ..Default::default()
                     });
@@ -206,15 +270,16 @@ fn collect_dom_errors(
 
                     diags.push(Diagnostic {
                         range,
-                        severity: Some(DiagnosticSeverity::ERROR),
+                        severity: Some(severity),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range: other_range,
                             },
-                            message: "required by this key".into(),
+                            message: catalog.format(&Message::new("required-by-this-key")),
                         }])),
                         ..Default::default()
                     });
@@ -222,14 +287,15 @@ fn collect_dom_errors(
                     diags.push(Diagnostic {
                         range: other_range,
                         severity: Some(DiagnosticSeverity::HINT),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range,
                             },
-                            message: "table defined here".into(),
+                            message: catalog.format(&Message::new("table-defined-here")),
                         }])),
                         ..Default::default()
                     });
@@ -252,15 +318,16 @@ fn collect_dom_errors(
 
                     diags.push(Diagnostic {
                         range,
-                        severity: Some(DiagnosticSeverity::ERROR),
+                        severity: Some(severity),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range: other_range,
                             },
-                            message: "required by this key".into(),
+                            message: catalog.format(&Message::new("required-by-this-key")),
                         }])),
                         ..Default::default()
                     });
@@ -268,20 +335,20 @@ fn collect_dom_errors(
                     diags.push(Diagnostic {
                         range: other_range,
                         severity: Some(DiagnosticSeverity::HINT),
+                        code: code.clone(),
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: catalog.format(&error.message()),
                         related_information: Some(Vec::from([DiagnosticRelatedInformation {
                             location: Location {
                                 uri: document_url.clone(),
                                 range,
                             },
-                            message: "array of tables defined here".into(),
+                            message: catalog.format(&Message::new("array-of-tables-defined-here")),
                         }])),
                         ..Default::default()
                     });
                 }
-                taplo::dom::Error::InvalidEscapeSequence { string: _ }
-                | taplo::dom::Error::Query(_) => {}
+                taplo::dom::Error::InvalidEscapeSequence { .. } | taplo::dom::Error::Query(_) => {}
                 taplo::dom::Error::UnexpectedSyntax { syntax } => {
                     tracing::error!("unexpected syntax in dom: {syntax:#?}");
                 }
@@ -296,6 +363,8 @@ async fn collect_schema_errors<E: Environment>(
     doc: &DocumentState,
     dom: &Node,
     document_url: &Url,
+    catalog: &Catalog,
+    filter: &DiagnosticFilter,
     diags: &mut Vec<Diagnostic>,
 ) {
     if !ws.config.schema.enabled {
@@ -312,32 +381,148 @@ async fn collect_schema_errors<E: Environment>(
 
         match ws.schemas.validate_root(&schema_association.url, dom).await {
             Ok(errors) => diags.extend(errors.into_iter().flat_map(|err| {
+                let data = coercion_fix(doc, dom, catalog, &err).and_then(QuickFix::into_data);
+
+                let message = catalog.format(&err.message());
+
                 let ranges = if let Some(KeyOrIndex::Key(k)) = err.keys.into_iter().last() {
                     Either::Left(k.text_ranges())
                 } else {
                     Either::Right(err.node.text_ranges())
                 };
 
-                let error = err.error;
-
-                ranges.map(move |range| {
+                ranges.filter_map(move |range| {
+                    let severity =
+                        filter.severity(NodeValidationError::CODE, range, Severity::Error)?;
                     let range = doc.mapper.range(range).unwrap_or_default().into_lsp();
-                    Diagnostic {
+                    Some(Diagnostic {
                         range,
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        code: None,
+                        severity: Some(lsp_severity(severity)),
+                        code: Some(NumberOrString::String(NodeValidationError::CODE.into())),
                         code_description: None,
                         source: Some("Even Better TOML".into()),
-                        message: error.to_string(),
+                        message: message.clone(),
                         related_information: None,
                         tags: None,
-                        data: None,
-                    }
+                        data: data.clone(),
+                    })
                 })
             })),
             Err(error) => {
                 tracing::error!(?error, "schema validation failed");
             }
         }
+
+        match ws.schemas.deprecations(&schema_association.url, dom).await {
+            Ok(deprecations) => diags.extend(deprecations.into_iter().flat_map(|deprecation| {
+                let data =
+                    deprecation_fix(doc, catalog, &deprecation).and_then(QuickFix::into_data);
+                let message = catalog.format(&deprecation.message());
+
+                let ranges: Vec<_> = deprecation
+                    .key()
+                    .map(|k| k.text_ranges().collect())
+                    .unwrap_or_default();
+
+                ranges.into_iter().filter_map(move |range| {
+                    let severity = filter.severity(Deprecation::CODE, range, Severity::Warning)?;
+                    Some(Diagnostic {
+                        range: doc.mapper.range(range).unwrap_or_default().into_lsp(),
+                        severity: Some(lsp_severity(severity)),
+                        code: Some(NumberOrString::String(Deprecation::CODE.into())),
+                        code_description: None,
+                        source: Some("Even Better TOML".into()),
+                        message: message.clone(),
+                        related_information: None,
+                        tags: Some(vec![DiagnosticTag::DEPRECATED]),
+                        data: data.clone(),
+                    })
+                })
+            })),
+            Err(error) => {
+                tracing::error!(?error, "failed to collect deprecated keys");
+            }
+        }
+    }
+}
+
+fn lsp_severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Information => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
+        Severity::Off => unreachable!("diagnostics that are off are not reported"),
+    }
+}
+
+/// A quick fix that converts the value to the expected type if it is lossless.
+fn coercion_fix(
+    doc: &DocumentState,
+    dom: &Node,
+    catalog: &Catalog,
+    err: &NodeValidationError,
+) -> Option<QuickFix> {
+    let (kind, text) = err.coercion()?;
+
+    let mut rewrite = Rewrite::new(dom.clone()).ok()?;
+    rewrite.replace_value(&err.keys, &text).ok()?;
+
+    Some(QuickFix {
+        title: catalog.format(&Message::new("quick-fix-convert").with_arg("kind", kind)),
+        preferred: true,
+        edits: rewrite
+            .patches()
+            .iter()
+            .filter_map(|patch| match &patch.kind {
+                PendingPatchKind::Replace(replace) => Some(TextEdit {
+                    range: doc.mapper.range(patch.range)?.into_lsp(),
+                    new_text: replace.to_string(),
+                }),
+                _ => None,
+            })
+            .collect(),
+    })
+}
+
+/// A quick fix that renames or moves a deprecated key to its replacement.
+///
+/// The replacement is relative to the table of the deprecated key,
+/// so replacing every occurrence of the key with the (possibly dotted)
+/// replacement moves the value to the new location.
+fn deprecation_fix(
+    doc: &DocumentState,
+    catalog: &Catalog,
+    deprecation: &Deprecation,
+) -> Option<QuickFix> {
+    let replacement = deprecation.replacement.as_ref()?;
+    let key = deprecation.key()?;
+
+    if !deprecation.can_replace(&doc.dom) {
+        return None;
     }
+
+    // Moving a key to another table restructures the document.
+    let rename = replacement.len() == 1;
+
+    let title = if rename {
+        Message::new("quick-fix-rename")
+    } else {
+        Message::new("quick-fix-move")
+    };
+    let title = catalog.format(&title.with_arg("key", replacement.dotted()));
+
+    Some(QuickFix {
+        title,
+        preferred: rename,
+        edits: key
+            .text_ranges()
+            .map(|range| {
+                Some(TextEdit {
+                    range: doc.mapper.range(range)?.into_lsp(),
+                    new_text: replacement.dotted().to_string(),
+                })
+            })
+            .collect::<Option<_>>()?,
+    })
 }
@@ -39,3 +39,9 @@ pub(crate) use rename::*;
 
 mod conversion;
 pub(crate) use conversion::*;
+
+mod definition;
+pub(crate) use definition::*;
+
+mod code_action;
+pub(crate) use code_action::*;
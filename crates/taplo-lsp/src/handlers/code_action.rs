@@ -0,0 +1,44 @@
+use crate::{diagnostics::QuickFix, world::World};
+use lsp_async_stub::{rpc::Error, Context, Params};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    WorkspaceEdit,
+};
+use std::collections::HashMap;
+use taplo_common::environment::Environment;
+
+#[tracing::instrument(skip_all)]
+pub async fn code_action<E: Environment>(
+    _context: Context<World<E>>,
+    params: Params<CodeActionParams>,
+) -> Result<Option<CodeActionResponse>, Error> {
+    let p = params.required()?;
+    let document_uri = p.text_document.uri;
+
+    let actions: CodeActionResponse = p
+        .context
+        .diagnostics
+        .into_iter()
+        .filter_map(|diag| {
+            let fix: QuickFix = serde_json::from_value(diag.data.clone()?).ok()?;
+
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: fix.title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diag]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(document_uri.clone(), fix.edits)])),
+                    ..Default::default()
+                }),
+                is_preferred: fix.preferred.then_some(true),
+                ..Default::default()
+            }))
+        })
+        .collect();
+
+    if actions.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(actions))
+}
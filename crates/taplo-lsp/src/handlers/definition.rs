@@ -0,0 +1,299 @@
+use crate::world::World;
+use lsp_async_stub::rpc::Error;
+use lsp_async_stub::util::{LspExt, Position};
+use lsp_async_stub::{Context, Params};
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, ReferenceParams};
+use taplo::{
+    dom::{
+        index::{DefinitionKind, KeyDefinition, KeyIndex},
+        Keys,
+    },
+    rowan::TextSize,
+    syntax::SyntaxNode,
+};
+use taplo_common::environment::Environment;
+
+#[tracing::instrument(skip_all)]
+pub async fn goto_definition<E: Environment>(
+    context: Context<World<E>>,
+    params: Params<GotoDefinitionParams>,
+) -> Result<Option<GotoDefinitionResponse>, Error> {
+    let p = params.required()?;
+    let document_uri = p.text_document_position_params.text_document.uri;
+
+    let workspaces = context.workspaces.read().await;
+    let ws = workspaces.by_document(&document_uri);
+    let doc = match ws.document(&document_uri) {
+        Ok(d) => d,
+        Err(error) => {
+            tracing::debug!(%error, "failed to get document from workspace");
+            return Ok(None);
+        }
+    };
+
+    let position = p.text_document_position_params.position;
+    let offset = match doc.mapper.offset(Position::from_lsp(position)) {
+        Some(ofs) => ofs,
+        None => {
+            tracing::error!(?position, "document position not found");
+            return Ok(None);
+        }
+    };
+
+    let root = doc.parse.clone().into_syntax();
+    let keys = match key_at(&doc.keys, &root, offset) {
+        Some((keys, _)) => keys,
+        None => return Ok(None),
+    };
+
+    Ok(find_definition(&doc.keys, &keys)
+        .and_then(|definition| doc.mapper.range(definition.range))
+        .map(|range| {
+            GotoDefinitionResponse::Scalar(Location {
+                uri: document_uri.clone(),
+                range: range.into_lsp(),
+            })
+        }))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn references<E: Environment>(
+    context: Context<World<E>>,
+    params: Params<ReferenceParams>,
+) -> Result<Option<Vec<Location>>, Error> {
+    let p = params.required()?;
+    let document_uri = p.text_document_position.text_document.uri;
+
+    let workspaces = context.workspaces.read().await;
+    let ws = workspaces.by_document(&document_uri);
+    let doc = match ws.document(&document_uri) {
+        Ok(d) => d,
+        Err(error) => {
+            tracing::debug!(%error, "failed to get document from workspace");
+            return Ok(None);
+        }
+    };
+
+    let position = p.text_document_position.position;
+    let offset = match doc.mapper.offset(Position::from_lsp(position)) {
+        Some(ofs) => ofs,
+        None => {
+            tracing::error!(?position, "document position not found");
+            return Ok(None);
+        }
+    };
+
+    let root = doc.parse.clone().into_syntax();
+    let keys = match key_at(&doc.keys, &root, offset) {
+        Some((keys, _)) => keys,
+        None => return Ok(None),
+    };
+
+    let definition_range = find_definition(&doc.keys, &keys).map(|d| d.range);
+
+    Ok(Some(
+        find_references(&doc.keys, &keys)
+            .into_iter()
+            .filter(|d| p.context.include_declaration || Some(d.range) != definition_range)
+            .filter_map(|d| doc.mapper.range(d.range))
+            .map(|range| Location {
+                uri: document_uri.clone(),
+                range: range.into_lsp(),
+            })
+            .collect(),
+    ))
+}
+
+/// The key definition at the given offset with its full path.
+fn key_at<'i>(
+    index: &'i KeyIndex,
+    root: &SyntaxNode,
+    offset: TextSize,
+) -> Option<(Keys, &'i KeyDefinition)> {
+    let keys = index.keys_at(root, offset)?;
+    let definition = index
+        .definitions(&keys)
+        .iter()
+        .find(|d| d.range.contains_inclusive(offset))?;
+
+    Some((keys, definition))
+}
+
+/// The definition that goto-definition should jump to.
+///
+/// Table headers take precedence over entries,
+/// if the key path is only used in dotted keys (a pseudo-table)
+/// the first definition is returned.
+fn find_definition<'i>(index: &'i KeyIndex, keys: &Keys) -> Option<&'i KeyDefinition> {
+    let definitions = index.definitions(keys);
+
+    definitions
+        .iter()
+        .find(|d| {
+            matches!(
+                d.kind,
+                DefinitionKind::Table | DefinitionKind::ArrayOfTables
+            )
+        })
+        .or_else(|| definitions.iter().find(|d| d.kind == DefinitionKind::Value))
+        .or_else(|| definitions.first())
+}
+
+/// All definitions of the given key path and all direct entries
+/// that contribute to it in the order they appear in the document.
+///
+/// Array indices are ignored, so the references of an array of tables
+/// include the entries of all of its items.
+fn find_references<'i>(index: &'i KeyIndex, keys: &Keys) -> Vec<&'i KeyDefinition> {
+    let keys = without_indices(keys);
+
+    let mut references: Vec<_> = index
+        .iter()
+        .filter(|(k, _)| {
+            let k = without_indices(k);
+            k == keys || (k.len() == keys.len() + 1 && k.skip_right(1) == keys)
+        })
+        .flat_map(|(_, definitions)| definitions)
+        .collect();
+
+    references.sort_by_key(|d| d.range.start());
+    references
+}
+
+fn without_indices(keys: &Keys) -> Keys {
+    Keys::new(keys.iter().filter(|k| k.is_key()).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_definition, find_references, key_at};
+    use taplo::{
+        dom::{
+            index::{DefinitionKind, KeyIndex},
+            Keys,
+        },
+        parser::parse,
+    };
+
+    fn index(src: &str) -> KeyIndex {
+        KeyIndex::new(&parse(src).into_syntax())
+    }
+
+    fn keys(keys: &str) -> Keys {
+        keys.parse().unwrap()
+    }
+
+    fn texts<'s>(src: &'s str, index: &KeyIndex, keys: &Keys) -> Vec<&'s str> {
+        find_references(index, keys)
+            .into_iter()
+            .map(|d| &src[d.range])
+            .collect()
+    }
+
+    #[test]
+    fn tables() {
+        let src = r#"
+[package]
+name = "a"
+
+[dependencies]
+serde = "1"
+
+[package.metadata]
+docs = true
+"#;
+        let index = index(src);
+
+        let definition = find_definition(&index, &keys("package")).unwrap();
+        assert_eq!(definition.kind, DefinitionKind::Table);
+        assert_eq!(&src[definition.range], "package");
+
+        assert_eq!(
+            texts(src, &index, &keys("package")),
+            ["package", "name", "package", "metadata"]
+        );
+        assert_eq!(
+            texts(src, &index, &keys("dependencies")),
+            ["dependencies", "serde"]
+        );
+
+        let root = parse(src).into_syntax();
+        let offset = src.find("docs").unwrap().try_into().unwrap();
+        assert_eq!(
+            key_at(&index, &root, offset).unwrap().0.dotted(),
+            "package.metadata.docs"
+        );
+    }
+
+    #[test]
+    fn dotted_keys() {
+        let src = r"
+a.b = 1
+a.c.d = 2
+
+[table]
+x = { y.z = 3 }
+";
+        let index = index(src);
+
+        let definition_a = find_definition(&index, &keys("a")).unwrap();
+        assert_eq!(definition_a.kind, DefinitionKind::Dotted);
+        assert_eq!(definition_a.range.start(), 1.into());
+
+        assert_eq!(texts(src, &index, &keys("a")), ["a", "b", "a", "c"]);
+        assert_eq!(
+            find_definition(&index, &keys("a.c.d")).unwrap().kind,
+            DefinitionKind::Value
+        );
+        assert_eq!(texts(src, &index, &keys("table.x.y")), ["y", "z"]);
+
+        let root = parse(src).into_syntax();
+        let offset = src.find("z = 3").unwrap().try_into().unwrap();
+        assert_eq!(
+            key_at(&index, &root, offset).unwrap().0.dotted(),
+            "table.x.y.z"
+        );
+    }
+
+    #[test]
+    fn arrays_of_tables() {
+        let src = r#"
+[[bin]]
+name = "a"
+
+[[bin]]
+"name" = "b"
+
+[bin.metadata]
+x = 1
+"#;
+        let index = index(src);
+
+        let definition = find_definition(&index, &keys("bin")).unwrap();
+        assert_eq!(definition.kind, DefinitionKind::ArrayOfTables);
+        assert_eq!(index.definitions(&keys("bin")).len(), 3);
+
+        assert_eq!(
+            texts(src, &index, &keys("bin")),
+            ["bin", "name", "bin", "\"name\"", "bin", "metadata"]
+        );
+
+        let root = parse(src).into_syntax();
+        let offset = src.rfind("x = 1").unwrap().try_into().unwrap();
+        assert_eq!(
+            key_at(&index, &root, offset).unwrap().0.dotted(),
+            "bin.1.metadata.x"
+        );
+
+        let offset = src.rfind("name").unwrap().try_into().unwrap();
+        let (keys, _) = key_at(&index, &root, offset).unwrap();
+        assert_eq!(keys.dotted(), "bin.1.name");
+        assert_eq!(find_references(&index, &keys).len(), 2);
+
+        let offset = src.rfind("metadata").unwrap().try_into().unwrap();
+        assert_eq!(
+            key_at(&index, &root, offset).unwrap().0.dotted(),
+            "bin.1.metadata"
+        );
+    }
+}
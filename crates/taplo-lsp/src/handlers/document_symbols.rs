@@ -156,7 +156,7 @@ fn symbols_for_value(
                 },
             });
         }
-        Node::Invalid(_) => {}
+        Node::Unknown(_) | Node::Invalid(_) => {}
     }
 }
 
@@ -4,6 +4,7 @@ use lsp_types::{
     DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
     PublishDiagnosticsParams,
 };
+use taplo::messages::Message;
 use taplo_common::{
     environment::Environment,
     schema::associations::{source, AssociationRule},
@@ -30,6 +31,11 @@ pub(crate) async fn document_open<E: Environment>(
     if let Some(pth) = context.env.to_file_path_normalized(&p.text_document.uri) {
         if !ws.taplo_config.is_included(&pth) {
             drop(workspaces);
+            let message = context
+                .init_config
+                .load()
+                .message_catalog
+                .format(&Message::new("document-excluded"));
             context
                 .write_notification::<notification::PublishDiagnostics, _>(Some(
                     PublishDiagnosticsParams {
@@ -40,7 +46,7 @@ pub(crate) async fn document_open<E: Environment>(
                             code: None,
                             code_description: None,
                             source: Some("Even Better TOML".into()),
-                            message: "this document has been excluded".into(),
+                            message,
                             related_information: None,
                             tags: None,
                             data: None,
@@ -57,7 +63,7 @@ pub(crate) async fn document_open<E: Environment>(
     let parse = taplo::parser::parse(&p.text_document.text);
     let mapper = Mapper::new_utf16(&p.text_document.text, false);
 
-    let dom = parse.clone().into_dom();
+    let (dom, keys) = parse.clone().into_dom_with_index();
 
     if ws.config.schema.enabled {
         ws.schemas
@@ -78,7 +84,12 @@ pub(crate) async fn document_open<E: Environment>(
 
     ws.documents.insert(
         p.text_document.uri.clone(),
-        DocumentState { parse, dom, mapper },
+        DocumentState {
+            parse,
+            dom,
+            mapper,
+            keys,
+        },
     );
 
     let ws_root = ws.root.clone();
@@ -108,6 +119,11 @@ pub(crate) async fn document_change<E: Environment>(
     if let Some(pth) = context.env.to_file_path_normalized(&p.text_document.uri) {
         if !ws.taplo_config.is_included(&pth) {
             drop(workspaces);
+            let message = context
+                .init_config
+                .load()
+                .message_catalog
+                .format(&Message::new("document-excluded"));
             context
                 .write_notification::<notification::PublishDiagnostics, _>(Some(
                     PublishDiagnosticsParams {
@@ -118,7 +134,7 @@ pub(crate) async fn document_change<E: Environment>(
                             code: None,
                             code_description: None,
                             source: Some("Even Better TOML".into()),
-                            message: "this document has been excluded".into(),
+                            message,
                             related_information: None,
                             tags: None,
                             data: None,
@@ -135,7 +151,7 @@ pub(crate) async fn document_change<E: Environment>(
     let parse = taplo::parser::parse(&change.text);
     let mapper = Mapper::new_utf16(&change.text, false);
 
-    let dom = parse.clone().into_dom();
+    let (dom, keys) = parse.clone().into_dom_with_index();
 
     if ws.config.schema.enabled {
         ws.schemas
@@ -146,7 +162,12 @@ pub(crate) async fn document_change<E: Environment>(
 
     ws.documents.insert(
         p.text_document.uri.clone(),
-        DocumentState { parse, dom, mapper },
+        DocumentState {
+            parse,
+            dom,
+            mapper,
+            keys,
+        },
     );
 
     let ws_root = ws.root.clone();
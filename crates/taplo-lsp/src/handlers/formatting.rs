@@ -1,9 +1,17 @@
-use lsp_async_stub::{rpc::Error, util::LspExt, Context, Params};
-use lsp_types::{DocumentFormattingParams, TextEdit};
+use std::path::Path;
+
+use lsp_async_stub::{
+    rpc::Error,
+    util::{LspExt, Position},
+    Context, Params,
+};
+use lsp_types::{
+    DocumentFormattingParams, DocumentOnTypeFormattingParams, FormattingOptions, TextEdit,
+};
 use taplo::formatter;
 use taplo_common::environment::Environment;
 
-use crate::World;
+use crate::{world::WorkspaceState, World};
 
 #[tracing::instrument(skip_all)]
 pub(crate) async fn format<E: Environment>(
@@ -32,23 +40,7 @@ pub(crate) async fn format<E: Environment>(
             ))
         })?;
 
-    let mut format_opts = formatter::Options {
-        indent_string: if p.options.insert_spaces {
-            " ".repeat(p.options.tab_size as usize)
-        } else {
-            "\t".into()
-        },
-        ..Default::default()
-    };
-
-    if let Some(v) = p.options.insert_final_newline {
-        format_opts.trailing_newline = v;
-    }
-
-    format_opts.update_camel(ws.config.formatter.clone());
-
-    ws.taplo_config
-        .update_format_options(&doc_path, &mut format_opts);
+    let format_opts = format_options(ws, &doc_path, &p.options);
 
     let scopes = ws.taplo_config.format_scopes(&doc_path);
     tracing::trace!(
@@ -77,3 +69,83 @@ pub(crate) async fn format<E: Environment>(
         })?,
     }]))
 }
+
+#[tracing::instrument(skip_all)]
+pub(crate) async fn format_on_type<E: Environment>(
+    context: Context<World<E>>,
+    params: Params<DocumentOnTypeFormattingParams>,
+) -> Result<Option<Vec<TextEdit>>, Error> {
+    let p = params.required()?;
+    let document_uri = p.text_document_position.text_document.uri;
+
+    let ch = match p.ch.chars().next() {
+        Some(ch) => ch,
+        None => return Ok(None),
+    };
+
+    let workspaces = context.workspaces.read().await;
+    let ws = workspaces.by_document(&document_uri);
+    let doc = match ws.document(&document_uri) {
+        Ok(d) => d,
+        Err(error) => {
+            tracing::debug!(%error, "failed to get document from workspace");
+            return Ok(None);
+        }
+    };
+
+    let doc_path = match context.env.to_file_path_normalized(&document_uri) {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+
+    let position = p.text_document_position.position;
+    let offset = match doc.mapper.offset(Position::from_lsp(position)) {
+        Some(ofs) => ofs,
+        None => {
+            tracing::error!(?position, "document position not found");
+            return Ok(None);
+        }
+    };
+
+    let format_opts = format_options(ws, &doc_path, &p.options);
+
+    let src = doc.parse.clone().into_syntax().to_string();
+
+    Ok(Some(
+        formatter::format_on_type(&src, offset, ch, format_opts)
+            .into_iter()
+            .filter_map(|edit| {
+                Some(TextEdit {
+                    range: doc.mapper.range(edit.range)?.into_lsp(),
+                    new_text: edit.text,
+                })
+            })
+            .collect(),
+    ))
+}
+
+fn format_options<E: Environment>(
+    ws: &WorkspaceState<E>,
+    doc_path: &Path,
+    options: &FormattingOptions,
+) -> formatter::Options {
+    let mut format_opts = formatter::Options {
+        indent_string: if options.insert_spaces {
+            " ".repeat(options.tab_size as usize)
+        } else {
+            "\t".into()
+        },
+        ..Default::default()
+    };
+
+    if let Some(v) = options.insert_final_newline {
+        format_opts.trailing_newline = v;
+    }
+
+    format_opts.update_camel(ws.config.formatter.clone());
+
+    ws.taplo_config
+        .update_format_options(doc_path, &mut format_opts);
+
+    format_opts
+}
@@ -6,13 +6,15 @@ use crate::world::WorkspaceState;
 use crate::World;
 use lsp_async_stub::{rpc::Error, Context, Params};
 use lsp_types::{
-    CompletionOptions, DocumentLinkOptions, FoldingRangeProviderCapability,
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CompletionOptions,
+    DocumentLinkOptions, DocumentOnTypeFormattingOptions, FoldingRangeProviderCapability,
     HoverProviderCapability, InitializedParams, OneOf, RenameOptions, SemanticTokensFullOptions,
     SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities,
     ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind,
     WorkDoneProgressOptions, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
 };
 use lsp_types::{InitializeParams, InitializeResult};
+use taplo::formatter;
 use taplo_common::environment::Environment;
 
 #[tracing::instrument(skip_all)]
@@ -77,9 +79,25 @@ pub async fn initialize<E: Environment>(
                 prepare_provider: Some(true),
                 work_done_progress_options: Default::default(),
             })),
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                work_done_progress_options: Default::default(),
+                resolve_provider: None,
+            })),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
             document_symbol_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: formatter::TRIGGER_CHARACTERS[0].into(),
+                more_trigger_character: Some(
+                    formatter::TRIGGER_CHARACTERS[1..]
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                ),
+            }),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
             completion_provider: Some(CompletionOptions {
                 resolve_provider: Some(false),
@@ -33,12 +33,16 @@ pub fn create_server<E: Environment>() -> Server<World<E>> {
         .on_request::<request::FoldingRangeRequest, _>(handlers::folding_ranges)
         .on_request::<request::DocumentSymbolRequest, _>(handlers::document_symbols)
         .on_request::<request::Formatting, _>(handlers::format)
+        .on_request::<request::OnTypeFormatting, _>(handlers::format_on_type)
         .on_request::<request::Completion, _>(handlers::completion)
         .on_request::<request::HoverRequest, _>(handlers::hover)
         .on_request::<request::DocumentLinkRequest, _>(handlers::links)
         .on_request::<request::SemanticTokensFullRequest, _>(handlers::semantic_tokens)
         .on_request::<request::PrepareRenameRequest, _>(handlers::prepare_rename)
         .on_request::<request::Rename, _>(handlers::rename)
+        .on_request::<request::GotoDefinition, _>(handlers::goto_definition)
+        .on_request::<request::References, _>(handlers::references)
+        .on_request::<request::CodeActionRequest, _>(handlers::code_action)
         .on_notification::<notification::Initialized, _>(handlers::initialized)
         .on_notification::<notification::DidOpenTextDocument, _>(handlers::document_open)
         .on_notification::<notification::DidChangeTextDocument, _>(handlers::document_change)
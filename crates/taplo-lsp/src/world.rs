@@ -10,7 +10,10 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use serde_json::json;
 use std::{sync::Arc, time::Duration};
-use taplo::{dom::Node, parser::Parse};
+use taplo::{
+    dom::{index::KeyIndex, Node},
+    parser::Parse,
+};
 use taplo_common::{
     config::Config,
     environment::Environment,
@@ -298,4 +301,5 @@ pub struct DocumentState {
     pub(crate) parse: Parse,
     pub(crate) dom: Node,
     pub(crate) mapper: Mapper,
+    pub(crate) keys: KeyIndex,
 }
@@ -0,0 +1,368 @@
+//! Typed views of common structures in `Cargo.toml` manifests.
+//!
+//! The views are built from the DOM and every value carries its range in the document.
+//! Values with unexpected types are skipped, the manifest is not validated.
+
+use crate::dom::{node::Key, Node};
+use rowan::TextRange;
+
+/// A value and its range in the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub range: TextRange,
+}
+
+/// A `Cargo.toml` manifest.
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    root: Node,
+}
+
+impl Manifest {
+    /// The root node must be the DOM of the entire manifest.
+    #[must_use]
+    pub fn new(root: Node) -> Self {
+        Self { root }
+    }
+
+    #[must_use]
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// All dependencies of the package, including the platform-specific ones
+    /// in `target` tables.
+    ///
+    /// Dependencies of the workspace are available via [`Workspace::dependencies`].
+    #[must_use]
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        let mut dependencies = Vec::new();
+        dependency_tables(&self.root, None, &mut dependencies);
+
+        if let Some(targets) = self.root.get("target").as_table() {
+            for (key, node) in targets.entries().read().iter() {
+                dependency_tables(node, Some(spanned_key(key)), &mut dependencies);
+            }
+        }
+
+        dependencies
+    }
+
+    /// The features defined in the `features` table.
+    #[must_use]
+    pub fn features(&self) -> Vec<Feature> {
+        let features = self.root.get("features");
+        let features = match features.as_table() {
+            Some(features) => features,
+            None => return Vec::new(),
+        };
+
+        let entries = features.entries().read();
+        entries
+            .iter()
+            .map(|(key, node)| Feature {
+                name: spanned_key(key),
+                enables: strings(node),
+                range: node_range(node),
+            })
+            .collect()
+    }
+
+    /// The `workspace` table, if any.
+    #[must_use]
+    pub fn workspace(&self) -> Option<Workspace> {
+        let workspace = self.root.get("workspace");
+        workspace.as_table()?;
+
+        let mut dependencies = Vec::new();
+        if let Some(table) = workspace.get("dependencies").as_table() {
+            for (key, node) in table.entries().read().iter() {
+                dependencies.push(Dependency::new(key, node, DependencyKind::Normal, None));
+            }
+        }
+
+        Some(Workspace {
+            members: strings(&workspace.get("members")),
+            exclude: strings(&workspace.get("exclude")),
+            dependencies,
+            range: node_range(&workspace),
+        })
+    }
+}
+
+/// The `workspace` table of a manifest.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    /// The paths or glob patterns of the members.
+    pub members: Vec<Spanned<String>>,
+    pub exclude: Vec<Spanned<String>>,
+    /// The dependencies in `workspace.dependencies` that members can inherit.
+    pub dependencies: Vec<Dependency>,
+    pub range: TextRange,
+}
+
+/// A feature and the features or dependencies it enables.
+#[derive(Debug, Clone)]
+pub struct Feature {
+    pub name: Spanned<String>,
+    pub enables: Vec<Spanned<String>>,
+    /// The range of the array of the feature.
+    pub range: TextRange,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+}
+
+impl DependencyKind {
+    /// The name of the table of the dependencies.
+    #[must_use]
+    pub fn table_name(self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "dependencies",
+            DependencyKind::Dev => "dev-dependencies",
+            DependencyKind::Build => "build-dependencies",
+        }
+    }
+}
+
+/// Where a dependency comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencySource {
+    /// A registry, crates.io unless specified.
+    Registry {
+        registry: Option<Spanned<String>>,
+    },
+    Path(Spanned<String>),
+    Git {
+        url: Spanned<String>,
+        reference: Option<GitReference>,
+    },
+    /// Inherited from the workspace with `workspace = true`.
+    Workspace,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitReference {
+    Branch(Spanned<String>),
+    Tag(Spanned<String>),
+    Rev(Spanned<String>),
+}
+
+/// A dependency in one of the dependency tables.
+///
+/// Dependencies can be a version string or a table,
+/// the missing fields of the former are empty.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    /// The key of the dependency.
+    pub name: Spanned<String>,
+    pub kind: DependencyKind,
+    /// The platform of a dependency in a `target` table.
+    pub target: Option<Spanned<String>>,
+    pub source: DependencySource,
+    pub version: Option<Spanned<String>>,
+    /// The name of the package if it is renamed.
+    pub package: Option<Spanned<String>>,
+    pub features: Vec<Spanned<String>>,
+    pub optional: Option<Spanned<bool>>,
+    pub default_features: Option<Spanned<bool>>,
+    /// The range of the value of the dependency.
+    pub range: TextRange,
+}
+
+impl Dependency {
+    fn new(key: &Key, node: &Node, kind: DependencyKind, target: Option<Spanned<String>>) -> Self {
+        let field = |name: &str| string(&node.get(name));
+
+        let source = if matches!(node.get("workspace").as_bool(), Some(b) if b.value()) {
+            DependencySource::Workspace
+        } else if let Some(path) = field("path") {
+            DependencySource::Path(path)
+        } else if let Some(url) = field("git") {
+            let reference = field("branch")
+                .map(GitReference::Branch)
+                .or_else(|| field("tag").map(GitReference::Tag))
+                .or_else(|| field("rev").map(GitReference::Rev));
+
+            DependencySource::Git { url, reference }
+        } else {
+            DependencySource::Registry {
+                registry: field("registry"),
+            }
+        };
+
+        Self {
+            name: spanned_key(key),
+            kind,
+            target,
+            source,
+            version: string(node).or_else(|| field("version")),
+            package: field("package"),
+            features: strings(&node.get("features")),
+            optional: boolean(&node.get("optional")),
+            default_features: boolean(&node.get("default-features"))
+                .or_else(|| boolean(&node.get("default_features"))),
+            range: node_range(node),
+        }
+    }
+
+    /// The name of the package, which differs from the name of renamed dependencies.
+    #[must_use]
+    pub fn package_name(&self) -> &str {
+        self.package.as_ref().unwrap_or(&self.name).value.as_str()
+    }
+}
+
+/// Collect the dependencies from the dependency tables of the given table.
+fn dependency_tables(
+    node: &Node,
+    target: Option<Spanned<String>>,
+    dependencies: &mut Vec<Dependency>,
+) {
+    let tables = [
+        ("dependencies", DependencyKind::Normal),
+        ("dev-dependencies", DependencyKind::Dev),
+        ("dev_dependencies", DependencyKind::Dev),
+        ("build-dependencies", DependencyKind::Build),
+        ("build_dependencies", DependencyKind::Build),
+    ];
+
+    for (name, kind) in tables {
+        if let Some(table) = node.get(name).as_table() {
+            for (key, node) in table.entries().read().iter() {
+                dependencies.push(Dependency::new(key, node, kind, target.clone()));
+            }
+        }
+    }
+}
+
+fn node_range(node: &Node) -> TextRange {
+    node.text_ranges().next().unwrap_or_default()
+}
+
+fn spanned_key(key: &Key) -> Spanned<String> {
+    Spanned {
+        value: key.value().to_string(),
+        range: key.text_ranges().next().unwrap_or_default(),
+    }
+}
+
+fn string(node: &Node) -> Option<Spanned<String>> {
+    Some(Spanned {
+        value: node.as_str()?.value().to_string(),
+        range: node_range(node),
+    })
+}
+
+fn boolean(node: &Node) -> Option<Spanned<bool>> {
+    Some(Spanned {
+        value: node.as_bool()?.value(),
+        range: node_range(node),
+    })
+}
+
+/// The strings in an array, other values are skipped.
+fn strings(node: &Node) -> Vec<Spanned<String>> {
+    match node.as_array() {
+        Some(array) => array.items().read().iter().filter_map(string).collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DependencyKind, DependencySource, GitReference, Manifest};
+    use crate::parser::parse;
+
+    #[test]
+    fn dependencies() {
+        let src = r#"
+[dependencies]
+serde = "1"
+local = { path = "../local", version = "0.1" }
+renamed = { package = "original", git = "https://example.com/repo", tag = "v1", features = ["a", "b"] }
+
+[dependencies.tokio]
+version = "1"
+default-features = false
+optional = true
+
+[dev-dependencies]
+inherited = { workspace = true }
+
+[target.'cfg(unix)'.build-dependencies]
+cc = "1"
+"#;
+        let manifest = Manifest::new(parse(src).into_dom());
+        let deps = manifest.dependencies();
+
+        let names: Vec<_> = deps.iter().map(|d| d.name.value.as_str()).collect();
+        assert_eq!(
+            names,
+            ["serde", "local", "renamed", "tokio", "inherited", "cc"]
+        );
+
+        assert_eq!(&src[deps[0].range], r#""1""#);
+        assert_eq!(deps[0].version.as_ref().unwrap().value, "1");
+        assert_eq!(&src[deps[0].name.range], "serde");
+
+        match &deps[1].source {
+            DependencySource::Path(path) => assert_eq!(&src[path.range], r#""../local""#),
+            source => panic!("unexpected source {source:?}"),
+        }
+
+        assert_eq!(deps[2].package_name(), "original");
+        assert!(matches!(
+            &deps[2].source,
+            DependencySource::Git {
+                reference: Some(GitReference::Tag(tag)),
+                ..
+            } if tag.value == "v1"
+        ));
+        let features: Vec<_> = deps[2].features.iter().map(|f| &src[f.range]).collect();
+        assert_eq!(features, [r#""a""#, r#""b""#]);
+
+        assert!(!deps[3].default_features.as_ref().unwrap().value);
+        assert_eq!(&src[deps[3].optional.as_ref().unwrap().range], "true");
+
+        assert_eq!(deps[4].kind, DependencyKind::Dev);
+        assert_eq!(deps[4].source, DependencySource::Workspace);
+
+        assert_eq!(deps[5].kind, DependencyKind::Build);
+        assert_eq!(deps[5].target.as_ref().unwrap().value, "cfg(unix)");
+    }
+
+    #[test]
+    fn features_and_workspace() {
+        let src = r#"
+[features]
+default = ["std"]
+std = ["serde/std", "dep:log"]
+
+[workspace]
+members = ["crates/*"]
+exclude = ["old"]
+
+[workspace.dependencies]
+log = "0.4"
+"#;
+        let manifest = Manifest::new(parse(src).into_dom());
+
+        let features = manifest.features();
+        assert_eq!(features[1].name.value, "std");
+        let enables: Vec<_> = features[1].enables.iter().map(|f| &f.value).collect();
+        assert_eq!(enables, ["serde/std", "dep:log"]);
+        assert_eq!(&src[features[0].range], r#"["std"]"#);
+
+        let workspace = manifest.workspace().unwrap();
+        assert_eq!(&src[workspace.members[0].range], r#""crates/*""#);
+        assert_eq!(workspace.exclude[0].value, "old");
+        assert_eq!(workspace.dependencies[0].name.value, "log");
+        assert!(manifest.dependencies().is_empty());
+    }
+}
@@ -0,0 +1,419 @@
+//! Structured [diagnostics](Diagnostic) with stable [error codes](ErrorCode),
+//! their severities and filtering them with
+//! [`#:taplo` comments](crate::parser::Directive).
+//!
+//! Diagnostics are identified by the [codes](crate::messages) of their messages,
+//! these are also called rules in configurations and comments.
+//!
+//! ```
+//! use taplo::{
+//!     diagnostics::{DiagnosticFilter, Severity},
+//!     parser::parse,
+//! };
+//!
+//! let parse = parse(
+//!     r#"
+//! a = 1
+//! #:taplo disable-next-line conflicting-keys
+//! a = 2
+//! a = 3
+//! "#,
+//! );
+//!
+//! let filter = DiagnosticFilter::new(&parse.directives)
+//!     .with_severities([("conflicting-keys", Severity::Warning)]);
+//!
+//! let severities: Vec<_> = parse
+//!     .into_dom()
+//!     .validate()
+//!     .unwrap_err()
+//!     .filter_map(|err| filter.error_severity(&err))
+//!     .collect();
+//!
+//! assert_eq!(severities, [Severity::Warning]);
+//! ```
+
+use crate::{
+    dom,
+    messages::Message,
+    parser::{self, Directive, DirectiveKind},
+    HashMap,
+};
+use rowan::TextRange;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+macro_rules! error_codes {
+    (
+        $(
+            $(#[$attr:meta])*
+            $variant:ident = $number:literal $name:literal,
+        )+
+    ) => {
+        /// Stable codes of the errors reported by Taplo.
+        ///
+        /// Every code has a number such as `E0001` and a name such as `conflicting-keys`,
+        /// the name is the code of the [message](crate::messages) of the error.
+        /// Neither of them change between versions.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ErrorCode {
+            $(
+                $(#[$attr])*
+                $variant,
+            )+
+        }
+
+        impl ErrorCode {
+            /// All error codes.
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant,)+];
+
+            /// The number of the code, e.g. `E0001`.
+            #[must_use]
+            pub fn number(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $number,)+
+                }
+            }
+
+            /// The name of the code, e.g. `conflicting-keys`.
+            #[must_use]
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $name,)+
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    // Semantic errors.
+    ConflictingKeys = "E0001" "conflicting-keys",
+    ExpectedTable = "E0002" "expected-table",
+    ExpectedArrayOfTables = "E0003" "expected-array-of-tables",
+    InvalidEscapeSequences = "E0004" "invalid-escape-sequences",
+    UnexpectedSyntax = "E0005" "unexpected-syntax",
+    InvalidQuery = "E0006" "invalid-query",
+    // Syntax errors.
+    ExpectedSymbol = "E0101" "expected-symbol",
+    UnexpectedSymbol = "E0102" "unexpected-symbol",
+    ExpectedIdentifier = "E0103" "expected-identifier",
+    UnexpectedIdentifier = "E0104" "unexpected-identifier",
+    ExpectedValue = "E0105" "expected-value",
+    ExpectedNewLine = "E0106" "expected-new-line",
+    UnexpectedToken = "E0107" "unexpected-token",
+    UnexpectedEof = "E0108" "unexpected-eof",
+    TrailingComma = "E0109" "trailing-comma",
+    InlineTableNewLine = "E0110" "inline-table-new-line",
+    InvalidUnderscores = "E0111" "invalid-underscores",
+    ZeroPaddedInteger = "E0112" "zero-padded-integer",
+    ZeroPaddedNumber = "E0113" "zero-padded-number",
+    InvalidCommentCharacter = "E0114" "invalid-comment-character",
+    InvalidStringCharacter = "E0115" "invalid-string-character",
+    InvalidControlCharacter = "E0116" "invalid-control-character",
+    InvalidEscapeSequence = "E0117" "invalid-escape-sequence",
+    /// Any other syntax error.
+    InvalidToml = "E0118" "invalid-toml",
+}
+
+impl ErrorCode {
+    /// The error code with the given number or name.
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL
+            .iter()
+            .copied()
+            .find(|c| c.number() == code || c.name() == code)
+    }
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.number().fmt(f)
+    }
+}
+
+/// An error in a document with all the information required to present it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    /// The range of the syntax that caused the error,
+    /// it is empty for errors that are not related to a document.
+    pub primary_span: TextRange,
+    /// Other parts of the document that are involved in the error.
+    pub related_spans: Vec<RelatedSpan>,
+    pub message: Message,
+}
+
+/// A part of the document that is involved in an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedSpan {
+    pub span: TextRange,
+    /// Explanation of how the span is involved, e.g. `required by this key`.
+    pub message: Message,
+}
+
+impl From<&dom::Error> for Diagnostic {
+    fn from(error: &dom::Error) -> Self {
+        let code = match error {
+            dom::Error::UnexpectedSyntax { .. } => ErrorCode::UnexpectedSyntax,
+            dom::Error::InvalidEscapeSequence { .. } => ErrorCode::InvalidEscapeSequences,
+            dom::Error::ConflictingKeys { .. } => ErrorCode::ConflictingKeys,
+            dom::Error::ExpectedTable { .. } => ErrorCode::ExpectedTable,
+            dom::Error::ExpectedArrayOfTables { .. } => ErrorCode::ExpectedArrayOfTables,
+            dom::Error::Query(_) => ErrorCode::InvalidQuery,
+        };
+
+        let related_spans = match error {
+            dom::Error::ConflictingKeys { other: key, .. } => {
+                key.text_ranges().next().map(|span| RelatedSpan {
+                    span,
+                    message: Message::new("other-key-defined-here"),
+                })
+            }
+            dom::Error::ExpectedTable { required_by, .. }
+            | dom::Error::ExpectedArrayOfTables { required_by, .. } => {
+                required_by.text_ranges().next().map(|span| RelatedSpan {
+                    span,
+                    message: Message::new("required-by-this-key"),
+                })
+            }
+            _ => None,
+        };
+
+        Diagnostic {
+            code,
+            primary_span: error.text_range().unwrap_or_default(),
+            related_spans: related_spans.into_iter().collect(),
+            message: error.message(),
+        }
+    }
+}
+
+impl From<dom::Error> for Diagnostic {
+    fn from(error: dom::Error) -> Self {
+        Diagnostic::from(&error)
+    }
+}
+
+impl From<&parser::Error> for Diagnostic {
+    fn from(error: &parser::Error) -> Self {
+        Diagnostic {
+            code: ErrorCode::from_code(error.message.code).unwrap_or(ErrorCode::InvalidToml),
+            primary_span: error.range,
+            related_spans: Vec::new(),
+            message: error.message.clone(),
+        }
+    }
+}
+
+impl From<parser::Error> for Diagnostic {
+    fn from(error: parser::Error) -> Self {
+        Diagnostic::from(&error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+    /// The diagnostic is not reported.
+    Off,
+}
+
+/// Decides the severity of diagnostics based on
+/// `#:taplo` comments and configured severities.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticFilter {
+    severities: HashMap<String, Severity>,
+    directives: Vec<Directive>,
+}
+
+impl DiagnosticFilter {
+    #[must_use]
+    pub fn new(directives: &[Directive]) -> Self {
+        Self {
+            severities: HashMap::default(),
+            directives: directives.to_vec(),
+        }
+    }
+
+    /// Override the severities of diagnostics by their codes,
+    /// later values take precedence.
+    #[must_use]
+    pub fn with_severities<C: Into<String>>(
+        mut self,
+        severities: impl IntoIterator<Item = (C, Severity)>,
+    ) -> Self {
+        self.severities
+            .extend(severities.into_iter().map(|(code, s)| (code.into(), s)));
+        self
+    }
+
+    /// The severity of a diagnostic with the given code at the given range,
+    /// or [`None`] if it should not be reported.
+    #[must_use]
+    pub fn severity(&self, code: &str, range: TextRange, default: Severity) -> Option<Severity> {
+        self.severity_impl(&[code], range, default)
+    }
+
+    /// The severity of a DOM error, see [`severity`](Self::severity).
+    #[must_use]
+    pub fn error_severity(&self, error: &dom::Error) -> Option<Severity> {
+        self.diagnostic_severity(&Diagnostic::from(error))
+    }
+
+    /// The severity of an error diagnostic, see [`severity`](Self::severity).
+    ///
+    /// Both the number and the name of the code can be used in the severities and comments.
+    #[must_use]
+    pub fn diagnostic_severity(&self, diagnostic: &Diagnostic) -> Option<Severity> {
+        self.severity_impl(
+            &[diagnostic.code.name(), diagnostic.code.number()],
+            diagnostic.primary_span,
+            Severity::Error,
+        )
+    }
+
+    fn severity_impl(
+        &self,
+        codes: &[&str],
+        range: TextRange,
+        default: Severity,
+    ) -> Option<Severity> {
+        let ignored = self
+            .directives
+            .iter()
+            .filter(|d| d.rules.is_empty() || d.rules.iter().any(|r| codes.contains(&r.as_str())))
+            .any(|d| match d.kind {
+                DirectiveKind::Ignore => true,
+                DirectiveKind::DisableNextLine { line } => line.contains_inclusive(range.start()),
+            });
+
+        if ignored {
+            return None;
+        }
+
+        let severity = codes
+            .iter()
+            .find_map(|code| self.severities.get(*code))
+            .copied()
+            .unwrap_or(default);
+
+        match severity {
+            Severity::Off => None,
+            severity => Some(severity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, DiagnosticFilter, ErrorCode, Severity};
+    use crate::{messages::DEFAULT_MESSAGES, parser::parse};
+
+    #[test]
+    fn error_codes() {
+        for (i, code) in ErrorCode::ALL.iter().enumerate() {
+            assert!(
+                ErrorCode::ALL[i + 1..]
+                    .iter()
+                    .all(|c| c.number() != code.number() && c.name() != code.name()),
+                "duplicate error code {code}"
+            );
+            // Query errors have a message for each `QueryError` variant.
+            assert!(
+                *code == ErrorCode::InvalidQuery
+                    || DEFAULT_MESSAGES.iter().any(|(c, _)| *c == code.name()),
+                "missing message for {}",
+                code.name()
+            );
+            assert_eq!(ErrorCode::from_code(code.number()), Some(*code));
+            assert_eq!(ErrorCode::from_code(code.name()), Some(*code));
+        }
+    }
+
+    #[test]
+    fn dom_error_diagnostics() {
+        let src = "a = 1\na = 2\n";
+        let doc = parse(src);
+        let errors: Vec<_> = doc.clone().into_dom().validate().unwrap_err().collect();
+
+        let diagnostic = Diagnostic::from(&errors[0]);
+        assert_eq!(diagnostic.code, ErrorCode::ConflictingKeys);
+        assert_eq!(diagnostic.code.to_string(), "E0001");
+        assert_eq!(diagnostic.message.to_string(), "conflicting keys");
+        assert_eq!(u32::from(diagnostic.primary_span.start()), 6);
+        assert_eq!(diagnostic.related_spans.len(), 1);
+        assert_eq!(u32::from(diagnostic.related_spans[0].span.start()), 0);
+
+        let filter =
+            DiagnosticFilter::new(&doc.directives).with_severities([("E0001", Severity::Warning)]);
+        assert_eq!(
+            filter.diagnostic_severity(&diagnostic),
+            Some(Severity::Warning)
+        );
+
+        let doc = parse(&format!("#:taplo ignore E0001\n{src}"));
+        let filter = DiagnosticFilter::new(&doc.directives);
+        assert!(doc
+            .into_dom()
+            .validate()
+            .unwrap_err()
+            .all(|err| filter.error_severity(&err).is_none()));
+    }
+
+    #[test]
+    fn suppression_comments() {
+        let parse = parse(
+            r#"#:taplo ignore expected-array-of-tables, zero-padded-number
+c = 1
+#:taplo disable-next-line
+c = 2
+
+d = 1 #:taplo disable-next-line conflicting-keys
+d = 2
+
+e = 1
+#:taplo disable-next-line expected-table
+e = 2
+
+[a]
+[[a]]
+"#,
+        );
+
+        let filter = DiagnosticFilter::new(&parse.directives);
+        let errors: Vec<_> = parse.into_dom().validate().unwrap_err().collect();
+
+        let reported: Vec<_> = errors
+            .iter()
+            .filter_map(|err| filter.error_severity(err).map(|s| (err.to_string(), s)))
+            .collect();
+
+        assert_eq!(
+            reported,
+            [("conflicting keys".to_string(), Severity::Error)],
+            "{errors:#?}"
+        );
+    }
+
+    #[test]
+    fn severities() {
+        let parse = parse("a = 1\na = 2\n");
+
+        let errors: Vec<_> = parse.clone().into_dom().validate().unwrap_err().collect();
+
+        let filter = DiagnosticFilter::new(&parse.directives)
+            .with_severities([("conflicting-keys", Severity::Warning)]);
+        assert_eq!(filter.error_severity(&errors[0]), Some(Severity::Warning));
+
+        let filter = filter.with_severities([("conflicting-keys", Severity::Off)]);
+        assert_eq!(filter.error_severity(&errors[0]), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Lossless conversions between the types of scalar values.
+
+use super::node::{DomNode, IntegerValue, Node};
+
+/// The types of scalar values a node can be converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarKind {
+    Bool,
+    Str,
+    Integer,
+    Float,
+}
+
+impl core::fmt::Display for ScalarKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScalarKind::Bool => "boolean".fmt(f),
+            ScalarKind::Str => "string".fmt(f),
+            ScalarKind::Integer => "integer".fmt(f),
+            ScalarKind::Float => "float".fmt(f),
+        }
+    }
+}
+
+/// The largest integer that is exactly representable as a float.
+const MAX_SAFE_INTEGER: u64 = 1 << f64::MANTISSA_DIGITS;
+
+/// Returns the canonical TOML representation of a finite float,
+/// which always contains a decimal point, e.g. `1.0` instead of `1`.
+fn float_repr(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value}.0")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Returns the TOML representation of the node converted to the given type,
+/// or [`None`] if the conversion would lose information.
+///
+/// Strings are only converted if they contain exactly what
+/// the value would be written as, so `"8080"` and `"true"` are converted,
+/// but `"08080"`, `" 8080"` or `"True"` are not.
+/// Numbers are converted to strings in their canonical decimal form,
+/// so `0x1F` becomes `"31"`, and non-finite floats are not converted.
+pub fn coerce(node: &Node, to: ScalarKind) -> Option<String> {
+    match (node, to) {
+        (Node::Str(s), ScalarKind::Bool) => match s.value() {
+            v @ ("true" | "false") => Some(v.to_string()),
+            _ => None,
+        },
+        (Node::Str(s), ScalarKind::Integer) => {
+            let value = s.value();
+            value
+                .parse::<i64>()
+                .ok()
+                .filter(|v| v.to_string() == value)
+                .map(|v| v.to_string())
+        }
+        (Node::Str(s), ScalarKind::Float) => {
+            let value = s.value();
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(float_repr)
+                .filter(|v| v == value)
+        }
+        (Node::Integer(int), ScalarKind::Str) => Some(format!(r#""{}""#, int.value())),
+        (Node::Float(float), ScalarKind::Str) => {
+            let value = float.value();
+            value
+                .is_finite()
+                .then(|| format!(r#""{}""#, float_repr(value)))
+        }
+        (Node::Bool(_) | Node::Date(_), ScalarKind::Str) => {
+            node.syntax().map(|s| format!(r#""{}""#, s))
+        }
+        (Node::Integer(int), ScalarKind::Float) => match int.value() {
+            IntegerValue::Positive(v) if v <= MAX_SAFE_INTEGER => Some(float_repr(v as f64)),
+            IntegerValue::Negative(v) if v.unsigned_abs() <= MAX_SAFE_INTEGER => {
+                Some(float_repr(v as f64))
+            }
+            _ => None,
+        },
+        (Node::Float(float), ScalarKind::Integer) => {
+            let value = float.value();
+            if value.is_finite() && value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER as f64 {
+                Some((value as i64).to_string())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coerce, ScalarKind};
+    use crate::parser::parse;
+
+    #[test]
+    fn coerce_lossless() {
+        let root = parse(
+            r#"
+port = "8080"
+enabled = 'true'
+ratio = "0.5"
+whole = "1.0"
+negative = "-2.0"
+count = 3
+version = 1.0
+hex = 0x1F
+separated = 1_000
+exponent = 1e2
+date = 1979-05-27
+"#,
+        )
+        .into_dom();
+
+        let get = |key: &str| root.get(key);
+
+        assert_eq!(coerce(&get("port"), ScalarKind::Integer).unwrap(), "8080");
+        assert_eq!(coerce(&get("enabled"), ScalarKind::Bool).unwrap(), "true");
+        assert_eq!(coerce(&get("ratio"), ScalarKind::Float).unwrap(), "0.5");
+        assert_eq!(coerce(&get("whole"), ScalarKind::Float).unwrap(), "1.0");
+        assert_eq!(coerce(&get("negative"), ScalarKind::Float).unwrap(), "-2.0");
+        assert_eq!(coerce(&get("count"), ScalarKind::Float).unwrap(), "3.0");
+        assert_eq!(coerce(&get("count"), ScalarKind::Str).unwrap(), r#""3""#);
+        assert_eq!(coerce(&get("version"), ScalarKind::Integer).unwrap(), "1");
+        assert_eq!(
+            coerce(&get("version"), ScalarKind::Str).unwrap(),
+            r#""1.0""#
+        );
+        assert_eq!(coerce(&get("hex"), ScalarKind::Str).unwrap(), r#""31""#);
+        assert_eq!(
+            coerce(&get("separated"), ScalarKind::Str).unwrap(),
+            r#""1000""#
+        );
+        assert_eq!(
+            coerce(&get("exponent"), ScalarKind::Str).unwrap(),
+            r#""100.0""#
+        );
+        assert_eq!(
+            coerce(&get("date"), ScalarKind::Str).unwrap(),
+            r#""1979-05-27""#
+        );
+    }
+
+    #[test]
+    fn coerce_lossy() {
+        let root = parse(
+            r#"
+port = "08080"
+padded = " 8080"
+enabled = "True"
+ratio = ".5"
+big = 9007199254740993
+fraction = 1.5
+infinite = inf
+not_a_number = nan
+"#,
+        )
+        .into_dom();
+
+        let get = |key: &str| root.get(key);
+
+        assert!(coerce(&get("port"), ScalarKind::Integer).is_none());
+        assert!(coerce(&get("padded"), ScalarKind::Integer).is_none());
+        assert!(coerce(&get("enabled"), ScalarKind::Bool).is_none());
+        assert!(coerce(&get("ratio"), ScalarKind::Float).is_none());
+        assert!(coerce(&get("big"), ScalarKind::Float).is_none());
+        assert!(coerce(&get("fraction"), ScalarKind::Integer).is_none());
+        assert!(coerce(&get("infinite"), ScalarKind::Str).is_none());
+        assert!(coerce(&get("not_a_number"), ScalarKind::Str).is_none());
+    }
+}
@@ -0,0 +1,255 @@
+//! Semantic comparison of DOM trees.
+
+use super::{
+    node::{ArrayKind, Node, TableKind},
+    Keys,
+};
+
+/// A semantic difference between two DOM trees.
+///
+/// Changes are reported at the topmost key that differs,
+/// a newly added table is reported once instead of
+/// once for each of its entries.
+#[derive(Debug, Clone)]
+pub enum Change {
+    Added { keys: Keys, node: Node },
+    Removed { keys: Keys, node: Node },
+    Modified { keys: Keys, old: Node, new: Node },
+}
+
+impl Change {
+    /// The full path of the changed value.
+    pub fn keys(&self) -> &Keys {
+        match self {
+            Change::Added { keys, .. } => keys,
+            Change::Removed { keys, .. } => keys,
+            Change::Modified { keys, .. } => keys,
+        }
+    }
+
+    /// The value after the change, if any.
+    pub fn new_node(&self) -> Option<&Node> {
+        match self {
+            Change::Added { node, .. } => Some(node),
+            Change::Removed { .. } => None,
+            Change::Modified { new, .. } => Some(new),
+        }
+    }
+
+    /// The value before the change, if any.
+    pub fn old_node(&self) -> Option<&Node> {
+        match self {
+            Change::Added { .. } => None,
+            Change::Removed { node, .. } => Some(node),
+            Change::Modified { old, .. } => Some(old),
+        }
+    }
+
+    /// Returns `true` if the change is [`Added`].
+    ///
+    /// [`Added`]: Change::Added
+    pub fn is_added(&self) -> bool {
+        matches!(self, Self::Added { .. })
+    }
+
+    /// Returns `true` if the change is [`Removed`].
+    ///
+    /// [`Removed`]: Change::Removed
+    pub fn is_removed(&self) -> bool {
+        matches!(self, Self::Removed { .. })
+    }
+
+    /// Returns `true` if the change is [`Modified`].
+    ///
+    /// [`Modified`]: Change::Modified
+    pub fn is_modified(&self) -> bool {
+        matches!(self, Self::Modified { .. })
+    }
+}
+
+/// Compare two DOM trees and return the changes required to get from `old` to `new`.
+///
+/// Tables and arrays of tables are compared entry by entry, all other values
+/// (including inline tables and arrays) are compared as a whole.
+///
+/// Only the values matter, so `1` and `0x1` or `"a"` and `'a'` are equal,
+/// and it does not matter whether a table was defined with a header or with dotted keys.
+pub fn diff(old: &Node, new: &Node) -> impl ExactSizeIterator<Item = Change> {
+    let mut changes = Vec::new();
+    diff_impl(Keys::empty(), old, new, &mut changes);
+    changes.into_iter()
+}
+
+/// Whether two nodes represent the same TOML value.
+pub(crate) fn value_eq(a: &Node, b: &Node) -> bool {
+    match (a, b) {
+        (Node::Table(a), Node::Table(b)) => {
+            let a = a.entries().read();
+            let b = b.entries().read();
+
+            a.len() == b.len()
+                && a.iter().all(|(key, a_node)| {
+                    b.lookup
+                        .get(key)
+                        .map(|b_node| value_eq(a_node, b_node))
+                        .unwrap_or(false)
+                })
+        }
+        (Node::Array(a), Node::Array(b)) => {
+            let a = a.items().read();
+            let b = b.items().read();
+
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| value_eq(a, b))
+        }
+        (Node::Bool(a), Node::Bool(b)) => a.value() == b.value(),
+        (Node::Str(a), Node::Str(b)) => a.value() == b.value(),
+        (Node::Integer(a), Node::Integer(b)) => a.value() == b.value(),
+        (Node::Float(a), Node::Float(b)) => {
+            a.value() == b.value() || (a.value().is_nan() && b.value().is_nan())
+        }
+        (Node::Date(a), Node::Date(b)) => a.value() == b.value(),
+        (Node::Unknown(a), Node::Unknown(b)) => a.text() == b.text(),
+        _ => false,
+    }
+}
+
+/// Whether the node is compared entry by entry instead of as a whole,
+/// these are the tables and arrays of tables that are not written inline.
+#[must_use]
+pub fn is_container(node: &Node) -> bool {
+    match node {
+        Node::Table(t) => t.kind() != TableKind::Inline,
+        Node::Array(arr) => arr.kind() == ArrayKind::Tables,
+        _ => false,
+    }
+}
+
+fn diff_impl(keys: Keys, old: &Node, new: &Node, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Node::Table(old_table), Node::Table(new_table))
+            if is_container(old) && is_container(new) =>
+        {
+            let old_entries = old_table.entries().read();
+            let new_entries = new_table.entries().read();
+
+            for (key, old_node) in old_entries.iter() {
+                match new_entries.lookup.get(key) {
+                    Some(new_node) => {
+                        diff_impl(keys.join(key.clone()), old_node, new_node, changes)
+                    }
+                    None => changes.push(Change::Removed {
+                        keys: keys.join(key.clone()),
+                        node: old_node.clone(),
+                    }),
+                }
+            }
+
+            for (key, new_node) in new_entries.iter() {
+                if !old_entries.lookup.contains_key(key) {
+                    changes.push(Change::Added {
+                        keys: keys.join(key.clone()),
+                        node: new_node.clone(),
+                    });
+                }
+            }
+        }
+        (Node::Array(old_array), Node::Array(new_array))
+            if is_container(old) && is_container(new) =>
+        {
+            let old_items = old_array.items().read();
+            let new_items = new_array.items().read();
+
+            for idx in 0..usize::max(old_items.len(), new_items.len()) {
+                match (old_items.get(idx), new_items.get(idx)) {
+                    (Some(old_node), Some(new_node)) => {
+                        diff_impl(keys.join(idx), old_node, new_node, changes)
+                    }
+                    (Some(old_node), None) => changes.push(Change::Removed {
+                        keys: keys.join(idx),
+                        node: old_node.clone(),
+                    }),
+                    (None, Some(new_node)) => changes.push(Change::Added {
+                        keys: keys.join(idx),
+                        node: new_node.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        _ => {
+            if !value_eq(old, new) {
+                changes.push(Change::Modified {
+                    keys,
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::parser::parse;
+
+    #[test]
+    fn diff_values() {
+        let old = parse(
+            r#"
+a = 1
+b = "b"
+[table]
+c = [1, 2]
+"#,
+        )
+        .into_dom();
+
+        let new = parse(
+            r#"
+a = 0x1
+b = 'b2'
+table.c = [1, 2]
+table.d = { e = true }
+"#,
+        )
+        .into_dom();
+
+        let changes = diff(&old, &new).collect::<Vec<_>>();
+
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].is_modified());
+        assert_eq!(changes[0].keys().dotted(), "b");
+        assert!(changes[1].is_added());
+        assert_eq!(changes[1].keys().dotted(), "table.d");
+    }
+
+    #[test]
+    fn diff_array_of_tables() {
+        let old = parse(
+            r#"
+[[bin]]
+name = "a"
+"#,
+        )
+        .into_dom();
+
+        let new = parse(
+            r#"
+[[bin]]
+name = "a"
+path = "a.rs"
+
+[[bin]]
+name = "b"
+"#,
+        )
+        .into_dom();
+
+        let changes = diff(&old, &new).collect::<Vec<_>>();
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].keys().dotted(), "bin.0.path");
+        assert_eq!(changes[1].keys().dotted(), "bin.1");
+    }
+}
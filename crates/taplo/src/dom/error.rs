@@ -1,32 +1,101 @@
 use super::node::Key;
-use crate::syntax::SyntaxElement;
+use crate::{messages::Message, syntax::SyntaxElement};
+use rowan::TextRange;
 use thiserror::Error;
 
 #[derive(Debug, Clone, Error)]
 pub enum Error {
-    #[error("the syntax was not expected here: {syntax:#?}")]
+    #[error("{}", self.message())]
     UnexpectedSyntax { syntax: SyntaxElement },
-    #[error("the string contains invalid escape sequence(s)")]
-    InvalidEscapeSequence { string: SyntaxElement },
-    #[error("conflicting keys")]
+    #[error("{}", self.message())]
+    InvalidEscapeSequence {
+        string: SyntaxElement,
+        /// The range of the invalid escape sequence.
+        range: TextRange,
+    },
+    #[error("{}", self.message())]
     ConflictingKeys { key: Key, other: Key },
-    #[error("expected table")]
+    #[error("{}", self.message())]
     ExpectedTable { not_table: Key, required_by: Key },
-    #[error("expected array of tables")]
+    #[error("{}", self.message())]
     ExpectedArrayOfTables {
         not_array_of_tables: Key,
         required_by: Key,
     },
-    #[error("{0}")]
+    #[error("{}", self.message())]
     Query(#[from] QueryError),
 }
 
+impl Error {
+    /// The range of the syntax that caused the error.
+    #[must_use]
+    pub fn text_range(&self) -> Option<TextRange> {
+        match self {
+            Error::UnexpectedSyntax { syntax } => Some(syntax.text_range()),
+            Error::InvalidEscapeSequence { range, .. } => Some(*range),
+            Error::ConflictingKeys { key, .. } => key.text_ranges().next(),
+            Error::ExpectedTable { not_table, .. } => not_table.text_ranges().next(),
+            Error::ExpectedArrayOfTables {
+                not_array_of_tables,
+                ..
+            } => not_array_of_tables.text_ranges().next(),
+            Error::Query(_) => None,
+        }
+    }
+
+    /// The message of the error,
+    /// it can be formatted with a [`Catalog`](crate::messages::Catalog) for translations.
+    #[must_use]
+    pub fn message(&self) -> Message {
+        match self {
+            Error::UnexpectedSyntax { syntax } => {
+                let kind = format!("{:?}", syntax.kind())
+                    .to_lowercase()
+                    .replace('_', " ");
+                Message::new("unexpected-syntax").with_arg("syntax", kind)
+            }
+            Error::InvalidEscapeSequence { .. } => Message::new("invalid-escape-sequences"),
+            Error::ConflictingKeys { key, .. } => {
+                Message::new("conflicting-keys").with_arg("key", key.value())
+            }
+            Error::ExpectedTable {
+                not_table,
+                required_by,
+            } => Message::new("expected-table")
+                .with_arg("key", not_table.value())
+                .with_arg("required_by", required_by.value()),
+            Error::ExpectedArrayOfTables {
+                not_array_of_tables,
+                required_by,
+            } => Message::new("expected-array-of-tables")
+                .with_arg("key", not_array_of_tables.value())
+                .with_arg("required_by", required_by.value()),
+            Error::Query(error) => error.message(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Error)]
 pub enum QueryError {
-    #[error("the key or index was not found")]
+    #[error("{}", self.message())]
     NotFound,
-    #[error("invalid glob pattern: {0}")]
+    #[error("{}", self.message())]
     InvalidGlob(#[from] globset::Error),
-    #[error("the given key is invalid: {0}")]
+    #[error("{}", self.message())]
     InvalidKey(crate::parser::Error),
 }
+
+impl QueryError {
+    /// The message of the error,
+    /// it can be formatted with a [`Catalog`](crate::messages::Catalog) for translations.
+    #[must_use]
+    pub fn message(&self) -> Message {
+        match self {
+            QueryError::NotFound => Message::new("key-not-found"),
+            QueryError::InvalidGlob(error) => Message::new("invalid-glob").with_arg("error", error),
+            QueryError::InvalidKey(error) => {
+                Message::new("invalid-key").with_arg("error", &error.message)
+            }
+        }
+    }
+}
@@ -1,15 +1,16 @@
 use super::{
     error::Error,
+    index::KeyIndex,
     node::{
         Array, ArrayInner, ArrayKind, Bool, BoolInner, DateTime, DateTimeInner, DomNode, Float,
         FloatInner, Integer, IntegerInner, IntegerRepr, Invalid, InvalidInner, Key, KeyInner, Node,
-        Str, StrInner, StrRepr, Table, TableInner, TableKind,
+        Str, StrInner, StrRepr, Table, TableInner, TableKind, Unknown, UnknownInner,
     },
     Comment, Keys,
 };
 use crate::{
     private::Sealed,
-    syntax::{SyntaxElement, SyntaxKind::*},
+    syntax::{SyntaxElement, SyntaxKind::*, SyntaxNode},
     util::{iter::ExactIterExt, shared::Shared},
 };
 use either::Either;
@@ -41,6 +42,7 @@ impl FromSyntax for Node {
             }
             ARRAY => Array::from_syntax(syntax).into(),
             INLINE_TABLE | ROOT => Table::from_syntax(syntax).into(),
+            UNKNOWN => Unknown::from_syntax(syntax).into(),
             _ => Invalid::from_syntax(syntax).into(),
         }
     }
@@ -79,6 +81,14 @@ impl FromSyntax for Table {
                     }
                 }
 
+                if let Some(syntax) = syntax.as_node() {
+                    add_conflict_errors(
+                        &table.clone().into(),
+                        syntax,
+                        &KeyIndex::inline_table(syntax),
+                    );
+                }
+
                 table
             }
             _ => {
@@ -356,6 +366,16 @@ impl FromSyntax for Str {
     }
 }
 
+impl FromSyntax for Unknown {
+    fn from_syntax(syntax: SyntaxElement) -> Self {
+        UnknownInner {
+            errors: Default::default(),
+            syntax: Some(syntax),
+        }
+        .into()
+    }
+}
+
 impl FromSyntax for Invalid {
     fn from_syntax(syntax: SyntaxElement) -> Self {
         let errors = Vec::from([Error::UnexpectedSyntax {
@@ -458,10 +478,16 @@ fn entry_from_syntax(syntax: SyntaxElement) -> (Key, Node) {
 }
 
 fn root_from_syntax(syntax: SyntaxElement) -> Table {
+    root_with_index(syntax).0
+}
+
+/// Create the root table and the index of the keys
+/// that was used to find the key conflicts in it.
+pub(crate) fn root_with_index(syntax: SyntaxElement) -> (Table, KeyIndex) {
     let node = match syntax.as_node() {
         Some(n) => n,
         None => {
-            return TableInner {
+            let table = TableInner {
                 errors: Vec::from([Error::UnexpectedSyntax {
                     syntax: syntax.clone(),
                 }])
@@ -471,7 +497,9 @@ fn root_from_syntax(syntax: SyntaxElement) -> Table {
                 kind: TableKind::Regular,
                 entries: Default::default(),
             }
-            .into()
+            .into();
+
+            return (table, KeyIndex::default());
         }
     };
 
@@ -510,25 +538,9 @@ fn root_from_syntax(syntax: SyntaxElement) -> Table {
                                                 .additional_syntaxes
                                                 .update(|s| s.push(syntax.clone()));
                                         }
-
-                                        if t.inner.kind != TableKind::Pseudo || !t.inner.header {
-                                            t.inner.errors.update(|errors| {
-                                                errors.push(Error::ConflictingKeys {
-                                                    key: key.clone(),
-                                                    other: k.clone(),
-                                                })
-                                            });
-                                        }
                                         current_table = t.clone();
                                     }
-                                    Some((k, _)) => {
-                                        current_table.inner.errors.update(|errors| {
-                                            errors.push(Error::ConflictingKeys {
-                                                key: key.clone(),
-                                                other: k.clone(),
-                                            })
-                                        });
-                                    }
+                                    Some(_) => {}
                                     None => {
                                         current_table.add_entry(key, new_table.clone().into());
                                         current_table = new_table;
@@ -557,32 +569,12 @@ fn root_from_syntax(syntax: SyntaxElement) -> Table {
                                             );
                                         }
 
-                                        match existing_node {
-                                            Node::Array(arr) => {
-                                                if arr.inner.kind != ArrayKind::Tables {
-                                                    existing_node.errors().update(|errors| {
-                                                        errors.push(Error::ExpectedArrayOfTables {
-                                                            not_array_of_tables: existing_key
-                                                                .clone(),
-                                                            required_by: key.clone(),
-                                                        })
-                                                    });
-                                                }
-
-                                                arr.inner.items.update(|items| {
-                                                    items.push(new_table.clone().into());
-                                                });
-
-                                                current_table = new_table;
-                                            }
-                                            existing_node => {
-                                                existing_node.errors().update(|errors| {
-                                                    errors.push(Error::ExpectedArrayOfTables {
-                                                        not_array_of_tables: existing_key.clone(),
-                                                        required_by: key.clone(),
-                                                    })
-                                                });
-                                            }
+                                        if let Node::Array(arr) = existing_node {
+                                            arr.inner.items.update(|items| {
+                                                items.push(new_table.clone().into());
+                                            });
+
+                                            current_table = new_table;
                                         }
                                     } else {
                                         let arr = Array::from_syntax(child.into());
@@ -610,7 +602,44 @@ fn root_from_syntax(syntax: SyntaxElement) -> Table {
         }
     }
 
-    root_table
+    let index = KeyIndex::new(node);
+    add_conflict_errors(&root_table.clone().into(), node, &index);
+
+    (root_table, index)
+}
+
+/// Add the errors of key conflicts to the deepest nodes along their paths,
+/// so that they are also reported when only a subtree is validated.
+///
+/// Conflicts in nested inline tables are skipped,
+/// the inline tables add them on their own.
+fn add_conflict_errors(root: &Node, syntax: &SyntaxNode, index: &KeyIndex) {
+    let inline_tables: Vec<_> = syntax
+        .descendants()
+        .skip(1)
+        .filter(|n| n.kind() == INLINE_TABLE)
+        .map(|n| n.text_range())
+        .collect();
+
+    for conflict in index.conflicts() {
+        if inline_tables
+            .iter()
+            .any(|range| range.contains_range(conflict.second.range))
+        {
+            continue;
+        }
+
+        let mut node = root.clone();
+        for key in conflict.keys.iter() {
+            match node.try_get(key) {
+                Ok(child) => node = child,
+                Err(_) => break,
+            }
+        }
+
+        node.errors()
+            .update(|errors| errors.push(Error::from(conflict.clone())));
+    }
 }
 
 /// Merge or create an intermediate dotted key in a top-level table or array.
@@ -632,30 +661,9 @@ fn merge_intermediate(key: Key, table: Table) -> Table {
 
             match existing_node {
                 Node::Table(existing_table) => {
-                    if !matches!(
-                        existing_table.inner.kind,
-                        TableKind::Regular | TableKind::Pseudo
-                    ) {
-                        existing_table.inner.errors.update(|errors| {
-                            errors.push(Error::ExpectedTable {
-                                not_table: existing_key.clone(),
-                                required_by: key.clone(),
-                            })
-                        });
-                    }
-
                     current_table = existing_table.clone();
                 }
                 Node::Array(existing_array) => {
-                    if existing_array.inner.kind != ArrayKind::Tables {
-                        existing_array.inner.errors.update(|errors| {
-                            errors.push(Error::ExpectedArrayOfTables {
-                                not_array_of_tables: existing_key.clone(),
-                                required_by: key.clone(),
-                            })
-                        });
-                    }
-
                     let items = existing_array.inner.items.read();
 
                     let item = items.iter().last();
@@ -672,12 +680,6 @@ fn merge_intermediate(key: Key, table: Table) -> Table {
                     }
                 }
                 _ => {
-                    current_table.inner.errors.update(|errors| {
-                        errors.push(Error::ExpectedTable {
-                            not_table: existing_key.clone(),
-                            required_by: key.clone(),
-                        })
-                    });
                     entries.add(key, new_table.clone().into());
                     current_table = new_table.clone();
                 }
@@ -1,5 +1,15 @@
-use super::{node::Node, KeyOrIndex};
-use crate::private::Sealed;
+use super::{
+    error::Error,
+    from_syntax::{keys_from_syntax, FromSyntax},
+    node::{Key, Node},
+    KeyOrIndex, Keys,
+};
+use crate::{
+    private::Sealed,
+    syntax::{SyntaxKind::*, SyntaxNode, SyntaxToken},
+    HashMap,
+};
+use rowan::{TextRange, TextSize};
 
 pub trait Index: Sealed + core::fmt::Display {
     #[doc(hidden)]
@@ -59,3 +69,480 @@ where
         (**self).index_into(v)
     }
 }
+
+/// How a key was defined at a position in the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DefinitionKind {
+    /// The last key of a table header, e.g. `b` in `[a.b]`.
+    Table,
+    /// The last key of an array of tables header, e.g. `b` in `[[a.b]]`.
+    ArrayOfTables,
+    /// A key before the last key of a table or array of tables header,
+    /// e.g. `a` in `[a.b]`.
+    HeaderPrefix,
+    /// A key before the last key of an entry, e.g. `a` in `a.b = 1`.
+    Dotted,
+    /// The last key of an entry, e.g. `b` in `a.b = 1`.
+    Value,
+}
+
+impl DefinitionKind {
+    /// Whether a definition of this kind is not allowed
+    /// after an `earlier` definition of the same key.
+    fn conflicts_with(self, earlier: DefinitionKind) -> bool {
+        use DefinitionKind::*;
+
+        match (earlier, self) {
+            (Value, _) | (_, Value) => true,
+            (ArrayOfTables, ArrayOfTables | HeaderPrefix) => false,
+            // Header prefixes in arrays of tables come after the first array of tables.
+            (HeaderPrefix, ArrayOfTables) => false,
+            (_, ArrayOfTables) | (ArrayOfTables, _) => true,
+            (Table, Table | Dotted) => true,
+            (Dotted, Table) => true,
+            (Table | Dotted | HeaderPrefix, _) => false,
+        }
+    }
+}
+
+/// A single occurrence of a key in the document.
+#[derive(Debug, Clone)]
+pub struct KeyDefinition {
+    pub kind: DefinitionKind,
+    /// The key as it appears in the document.
+    pub key: Key,
+    pub range: TextRange,
+}
+
+/// A key that was defined again in a way that is not allowed by TOML.
+#[derive(Debug, Clone)]
+pub struct KeyConflict {
+    /// The full path of the key.
+    pub keys: Keys,
+    /// The earlier definition that the key conflicts with.
+    pub first: KeyDefinition,
+    /// The definition that is not allowed.
+    pub second: KeyDefinition,
+}
+
+impl From<KeyConflict> for Error {
+    fn from(conflict: KeyConflict) -> Self {
+        use DefinitionKind::*;
+
+        match (conflict.first.kind, conflict.second.kind) {
+            (_, ArrayOfTables) => Error::ExpectedArrayOfTables {
+                not_array_of_tables: conflict.first.key,
+                required_by: conflict.second.key,
+            },
+            (Value | ArrayOfTables, HeaderPrefix | Dotted) => Error::ExpectedTable {
+                not_table: conflict.first.key,
+                required_by: conflict.second.key,
+            },
+            _ => Error::ConflictingKeys {
+                key: conflict.second.key,
+                other: conflict.first.key,
+            },
+        }
+    }
+}
+
+/// All definitions of keys in a document by their full paths.
+///
+/// The paths contain the indices of arrays of tables and arrays,
+/// so the `name` in the second `[[bin]]` table is at `bin.1.name`.
+/// Keys are compared by their values, `"a"` and `a` are the same key.
+///
+/// ```
+/// use taplo::{dom::index::KeyIndex, parser::parse};
+///
+/// let index = KeyIndex::new(&parse("[a]\nb = 1\n[a]\nb = 2\n").into_syntax());
+///
+/// assert_eq!(index.definitions(&"a.b".parse().unwrap()).len(), 2);
+/// assert_eq!(index.conflicts().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyIndex {
+    definitions: HashMap<Keys, Vec<KeyDefinition>>,
+    /// The paths in the order of their first definitions.
+    order: Vec<Keys>,
+    conflicts: Vec<KeyConflict>,
+    /// The number of tables in arrays of tables.
+    array_lengths: HashMap<Keys, usize>,
+    /// The paths of the tables that the headers start, by the offsets of the headers.
+    sections: Vec<(TextSize, Keys)>,
+}
+
+impl KeyIndex {
+    /// Collect the keys of a document from its root syntax node.
+    #[must_use]
+    pub fn new(root: &SyntaxNode) -> Self {
+        let mut index = Self::default();
+        let mut section = Keys::empty();
+
+        for child in root.children() {
+            match child.kind() {
+                kind @ (TABLE_HEADER | TABLE_ARRAY_HEADER) => {
+                    let keys = match child.first_child() {
+                        Some(keys) if keys.kind() == KEY => keys_from_syntax(&keys.into()),
+                        _ => continue,
+                    };
+
+                    let last = keys.len().saturating_sub(1);
+                    section = Keys::empty();
+
+                    for (i, key) in keys.enumerate() {
+                        section = section.join(normalized(&key));
+
+                        if i < last {
+                            index.add(&section, DefinitionKind::HeaderPrefix, key);
+                            if let Some(len) = index.array_lengths.get(&section) {
+                                section = section.join(len.saturating_sub(1));
+                            }
+                        } else if kind == TABLE_HEADER {
+                            index.add(&section, DefinitionKind::Table, key);
+                        } else {
+                            index.add(&section, DefinitionKind::ArrayOfTables, key);
+                            let len = index.array_lengths.entry(section.clone()).or_default();
+                            *len += 1;
+                            section = section.join(*len - 1);
+                        }
+                    }
+
+                    index
+                        .sections
+                        .push((child.text_range().start(), section.clone()));
+                }
+                ENTRY => index.add_entry(&section, &child),
+                _ => {}
+            }
+        }
+
+        index
+    }
+
+    /// Collect the keys of an inline table,
+    /// the paths are relative to the inline table.
+    pub(crate) fn inline_table(table: &SyntaxNode) -> Self {
+        let mut index = Self::default();
+        index.add_value(&Keys::empty(), table);
+        index
+    }
+
+    /// The definitions of the key at the given path in the order they appear in the document.
+    #[must_use]
+    pub fn definitions(&self, keys: &Keys) -> &[KeyDefinition] {
+        let keys = Keys::new(keys.iter().map(|k| match k {
+            KeyOrIndex::Key(k) => KeyOrIndex::Key(normalized(k)),
+            KeyOrIndex::Index(idx) => KeyOrIndex::Index(*idx),
+        }));
+
+        self.definitions
+            .get(&keys)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The path of the key at the given offset,
+    /// `root` must be the syntax tree the index was created from.
+    ///
+    /// ```
+    /// use taplo::{dom::index::KeyIndex, parser::parse};
+    ///
+    /// let root = parse("[[bin]]\n[[bin]]\nname = 'a'\n").into_syntax();
+    /// let index = KeyIndex::new(&root);
+    ///
+    /// let keys = index.keys_at(&root, 16.into()).unwrap();
+    /// assert_eq!(keys.dotted(), "bin.1.name");
+    /// ```
+    #[must_use]
+    pub fn keys_at(&self, root: &SyntaxNode, offset: TextSize) -> Option<Keys> {
+        let ident = root.token_at_offset(offset).find(|t| t.kind() == IDENT)?;
+        let key = ident.parent().filter(|k| k.kind() == KEY)?;
+        let idents = key_idents(&key);
+        let position = idents.iter().position(|i| *i == ident)?;
+        let mut node = key.parent()?;
+
+        if matches!(node.kind(), TABLE_HEADER | TABLE_ARRAY_HEADER) {
+            let start = node.text_range().start();
+            let section = self
+                .sections
+                .iter()
+                .find(|(s, _)| *s == start)
+                .map(|(_, keys)| keys)?;
+
+            return Some(section_prefix(section, position + 1));
+        }
+
+        // The path is collected from the key outwards.
+        let mut path: Vec<KeyOrIndex> = idents[..=position].iter().rev().map(ident_key).collect();
+
+        loop {
+            let parent = node.parent()?;
+
+            match parent.kind() {
+                ROOT => {
+                    let start = node.text_range().start();
+                    let idx = self.sections.partition_point(|(s, _)| *s <= start);
+                    if let Some((_, section)) = idx.checked_sub(1).map(|idx| &self.sections[idx]) {
+                        path.extend(section.iter().rev().cloned());
+                    }
+                    break;
+                }
+                ENTRY => {
+                    let key = parent.first_child().filter(|k| k.kind() == KEY)?;
+                    path.extend(key_idents(&key).iter().rev().map(ident_key));
+                }
+                ARRAY => {
+                    let idx = parent.children().position(|c| c == node)?;
+                    path.push(KeyOrIndex::Index(idx));
+                }
+                VALUE | INLINE_TABLE => {}
+                _ => return None,
+            }
+
+            node = parent;
+        }
+
+        path.reverse();
+        Some(Keys::new(path.into_iter()))
+    }
+
+    /// All paths with their definitions in the order of their first definitions.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&Keys, &[KeyDefinition])> {
+        self.order
+            .iter()
+            .map(|keys| (keys, self.definitions[keys].as_slice()))
+    }
+
+    /// The keys that are defined multiple times in conflicting ways,
+    /// in the order of the conflicting definitions.
+    ///
+    /// Each definition conflicts with at most one earlier definition.
+    #[must_use]
+    pub fn conflicts(&self) -> &[KeyConflict] {
+        &self.conflicts
+    }
+
+    fn add_entry(&mut self, section: &Keys, entry: &SyntaxNode) {
+        let keys = match entry.first_child() {
+            Some(keys) if keys.kind() == KEY => keys_from_syntax(&keys.into()),
+            _ => return,
+        };
+
+        let last = keys.len().saturating_sub(1);
+        let mut path = section.clone();
+
+        for (i, key) in keys.enumerate() {
+            path = path.join(normalized(&key));
+
+            if i < last {
+                self.add(&path, DefinitionKind::Dotted, key);
+            } else {
+                self.add(&path, DefinitionKind::Value, key);
+            }
+        }
+
+        if let Some(value) = entry.first_child().and_then(|k| k.next_sibling()) {
+            self.add_value(&path, &value);
+        }
+    }
+
+    /// Add the keys in inline tables and arrays.
+    fn add_value(&mut self, path: &Keys, value: &SyntaxNode) {
+        match value.kind() {
+            VALUE => {
+                if let Some(value) = value.first_child() {
+                    self.add_value(path, &value);
+                }
+            }
+            INLINE_TABLE => {
+                for entry in value.children().filter(|c| c.kind() == ENTRY) {
+                    self.add_entry(path, &entry);
+                }
+            }
+            ARRAY => {
+                for (idx, item) in value.children().enumerate() {
+                    self.add_value(&path.join(idx), &item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add(&mut self, keys: &Keys, kind: DefinitionKind, key: Key) {
+        let definition = KeyDefinition {
+            kind,
+            range: key.text_ranges().next().unwrap_or_default(),
+            key,
+        };
+
+        match self.definitions.get_mut(keys) {
+            Some(definitions) => {
+                let first = definitions
+                    .iter()
+                    .find(|d| kind.conflicts_with(d.kind))
+                    .or_else(|| {
+                        definitions.first().filter(|d| {
+                            kind == DefinitionKind::ArrayOfTables
+                                && d.kind == DefinitionKind::HeaderPrefix
+                        })
+                    });
+
+                if let Some(first) = first {
+                    self.conflicts.push(KeyConflict {
+                        keys: keys.clone(),
+                        first: first.clone(),
+                        second: definition.clone(),
+                    });
+                }
+
+                definitions.push(definition);
+            }
+            None => {
+                self.order.push(keys.clone());
+                self.definitions.insert(keys.clone(), vec![definition]);
+            }
+        }
+    }
+}
+
+/// Keys are compared by their text in [`Keys`], so the paths
+/// are built from keys without syntax.
+fn normalized(key: &Key) -> Key {
+    Key::new(key.value())
+}
+
+fn key_idents(key: &SyntaxNode) -> Vec<SyntaxToken> {
+    key.children_with_tokens()
+        .filter_map(|c| c.into_token())
+        .filter(|t| t.kind() == IDENT)
+        .collect()
+}
+
+fn ident_key(ident: &SyntaxToken) -> KeyOrIndex {
+    KeyOrIndex::Key(normalized(&Key::from_syntax(ident.clone().into())))
+}
+
+/// The beginning of a section path up to and including the given number of keys.
+fn section_prefix(section: &Keys, keys: usize) -> Keys {
+    let mut count = 0;
+    Keys::new(
+        section
+            .iter()
+            .take_while(|k| {
+                if k.is_key() {
+                    count += 1;
+                    count <= keys
+                } else {
+                    count < keys
+                }
+            })
+            .cloned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefinitionKind, KeyIndex};
+    use crate::{
+        dom::{node::Key, FromSyntax, Keys, Node},
+        parser::parse,
+        syntax::SyntaxKind::INLINE_TABLE,
+    };
+
+    #[test]
+    fn key_definitions() {
+        let src = r#"
+[[bin]]
+name = "a"
+
+[[bin]]
+"name" = "b"
+
+[package]
+metadata.x = { y = [{ z = 1 }] }
+"#;
+        let index = KeyIndex::new(&parse(src).into_syntax());
+        assert!(index.conflicts().is_empty());
+
+        let definitions = index.definitions(&"bin".parse().unwrap());
+        assert_eq!(definitions.len(), 2);
+        assert!(definitions
+            .iter()
+            .all(|d| d.kind == DefinitionKind::ArrayOfTables));
+
+        let definitions = index.definitions(&Key::new("bin").join(1usize).join(Key::new("name")));
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(&src[definitions[0].range], r#""name""#);
+
+        let definitions = index.definitions(&"package.metadata".parse().unwrap());
+        assert_eq!(definitions[0].kind, DefinitionKind::Dotted);
+
+        let definitions = index.definitions(
+            &"package.metadata.x.y"
+                .parse::<Keys>()
+                .unwrap()
+                .join(0usize)
+                .join(Key::new("z")),
+        );
+        assert_eq!(definitions[0].kind, DefinitionKind::Value);
+
+        assert_eq!(index.iter().next().unwrap().0.dotted(), "bin");
+    }
+
+    #[test]
+    fn key_conflicts() {
+        let invalid = [
+            "a = 1\na = 2",
+            "a = 1\na.b = 2",
+            "a = { b = 1, b = 2 }",
+            "[a]\n[a]",
+            "[a]\nb = 1\n[a.b]",
+            "a.b = 1\n[a]",
+            "[a]\n[[a]]",
+            "[[a]]\n[a]",
+            "[a.b]\n[[a]]",
+            "a = [1]\n[[a]]",
+            "a = { b = 1 }\n[a.c]",
+            "[a.b.c]\n[a]\nb.c.d = 1",
+        ];
+
+        for src in invalid {
+            let index = KeyIndex::new(&parse(src).into_syntax());
+            assert_eq!(index.conflicts().len(), 1, "{src}");
+            assert!(toml::from_str::<toml::Value>(src).is_err(), "{src}");
+        }
+
+        // Not checked against the `toml` crate, it rejects subtables
+        // of tables defined by dotted keys that are allowed since TOML 1.0.
+        let valid = [
+            "a.b = 1\na.c = 2",
+            "[a.b]\n[a]",
+            "[a]\nb.c = 1\n[a.b.d]",
+            "[[a]]\n[a.b]\n[[a]]\n[a.b]",
+            "[a.b.c]\n[a]\nb.d = 1",
+            "'a' = 1\n\"b\" = 2",
+        ];
+
+        for src in valid {
+            let index = KeyIndex::new(&parse(src).into_syntax());
+            assert!(index.conflicts().is_empty(), "{src}");
+        }
+    }
+
+    #[test]
+    fn conflict_errors_in_subtrees() {
+        let root = parse("[a]\nb = 1\nb = 2\n\n[c]\nd = { e = 1, e = 2 }\n").into_dom();
+        assert_eq!(root.validate().unwrap_err().count(), 2);
+        assert_eq!(root.get("a").validate().unwrap_err().count(), 1);
+        assert_eq!(root.get("c").validate().unwrap_err().count(), 1);
+
+        let syntax = parse("d = { e = 1, e = 2 }").into_syntax();
+        let inline_table = syntax
+            .descendants()
+            .find(|n| n.kind() == INLINE_TABLE)
+            .unwrap();
+        let table = Node::from_syntax(inline_table.into());
+        assert_eq!(table.validate().unwrap_err().count(), 1);
+    }
+}
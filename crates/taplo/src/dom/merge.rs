@@ -0,0 +1,603 @@
+//! Three-way merging of TOML documents.
+
+use super::{
+    diff::{diff, is_container, value_eq, Change},
+    node::{DomNode, Node, TableKind},
+    KeyOrIndex, Keys,
+};
+use crate::syntax::{SyntaxKind::*, SyntaxNode};
+use rowan::{Direction, TextRange};
+use std::ops::Range;
+use thiserror::Error;
+
+/// Changes to the same value on both sides that could not be merged.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub keys: Keys,
+    pub base: Option<Node>,
+    pub ours: Option<Node>,
+    pub theirs: Option<Node>,
+}
+
+impl Conflict {
+    /// The range of the value in the base document, if it exists there.
+    pub fn base_range(&self) -> Option<TextRange> {
+        self.base.as_ref().and_then(|n| n.text_ranges().next())
+    }
+
+    /// The range of the value in our document, if it exists there.
+    pub fn ours_range(&self) -> Option<TextRange> {
+        self.ours.as_ref().and_then(|n| n.text_ranges().next())
+    }
+
+    /// The range of the value in their document, if it exists there.
+    pub fn theirs_range(&self) -> Option<TextRange> {
+        self.theirs.as_ref().and_then(|n| n.text_ranges().next())
+    }
+}
+
+/// The result of a three-way merge.
+///
+/// The merged document is our document with all the
+/// non-conflicting changes from their document applied,
+/// conflicting values are left as they are in our document.
+///
+/// The merged document is available via [`ToString::to_string`],
+/// it contains no conflict markers, so it should not be
+/// used as a resolved document if there were conflicts.
+#[derive(Debug)]
+pub struct Merge {
+    source: String,
+    newline: &'static str,
+    patches: Vec<(Range<usize>, String)>,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Returns `true` if there were no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    fn patch(&mut self, mut range: Range<usize>, mut text: String) {
+        if range.is_empty() {
+            // Insertions must not end up in removed ranges.
+            if let Some((removed, _)) = self
+                .patches
+                .iter()
+                .find(|(r, _)| r.start < range.start && range.start < r.end)
+            {
+                range = removed.end..removed.end;
+
+                // The removed range ends at a line start.
+                if let Some(t) = text.strip_prefix(self.newline) {
+                    text = t.to_string() + self.newline;
+                }
+            }
+
+            if let Some((_, existing)) = self
+                .patches
+                .iter_mut()
+                .find(|(r, _)| r.is_empty() && r.start == range.start)
+            {
+                *existing += &text;
+                return;
+            }
+        } else if self.patches.iter().any(|(r, _)| *r == range) {
+            return;
+        }
+
+        self.patches.push((range, text));
+    }
+
+    fn replace(&mut self, node: &Node, with: &Node) {
+        if let Some(syntax) = node.syntax() {
+            self.patch(std_range(syntax.text_range()), value_text(with));
+        }
+    }
+
+    fn remove(&mut self, node: &Node) {
+        match node {
+            Node::Table(table) if is_container(node) => {
+                if let Some(header) = table
+                    .syntax()
+                    .filter(|s| matches!(s.kind(), TABLE_HEADER | TABLE_ARRAY_HEADER))
+                {
+                    let range = self.line_range(header.text_range());
+                    self.patch(range, String::new());
+                }
+
+                for (_, entry) in table.entries().read().iter() {
+                    self.remove(entry);
+                }
+            }
+            Node::Array(array) if is_container(node) => {
+                for item in array.items().read().iter() {
+                    self.remove(item);
+                }
+            }
+            _ => {
+                if let Some(entry) = node
+                    .syntax()
+                    .and_then(|s| s.ancestors().find(|a| a.kind() == ENTRY))
+                {
+                    let range = self.line_range(entry.text_range());
+                    self.patch(range, String::new());
+                }
+            }
+        }
+    }
+
+    /// Insert a new value into our document, returns `false`
+    /// if there was no place to insert it.
+    fn insert(&mut self, root: &Node, keys: &Keys, node: &Node) -> bool {
+        if is_container(node) && !is_dotted(node) {
+            return self.insert_section(root, keys, node);
+        }
+
+        // Find the closest table that has its own place in the document,
+        // the value is added there with dotted keys if needed.
+        let mut table_keys = keys.skip_right(1);
+        let table = loop {
+            match root.path(&table_keys) {
+                Some(Node::Table(t)) if t.kind() == TableKind::Pseudo && !table_keys.is_empty() => {
+                    table_keys = table_keys.skip_right(1);
+                }
+                Some(Node::Table(t)) if t.kind() == TableKind::Regular => break t,
+                _ => return false,
+            }
+        };
+
+        let mut lines = Vec::new();
+        entry_lines(keys.skip_left(table_keys.len()), node, &mut lines);
+        let lines = lines.join(self.newline);
+
+        let syntax = match table.syntax().and_then(|s| s.as_node()) {
+            Some(s) => s.clone(),
+            None => return false,
+        };
+
+        match syntax.kind() {
+            ROOT => {
+                let last_entry = syntax
+                    .children()
+                    .take_while(|n| !is_header(n))
+                    .filter(|n| n.kind() == ENTRY)
+                    .last();
+
+                if let Some(entry) = last_entry {
+                    let offset = self.line_end(entry.text_range());
+                    self.patch(offset..offset, format!("{}{lines}", self.newline));
+                } else if let Some(header) = syntax.children().find(is_header) {
+                    let offset = self.line_range(header.text_range()).start;
+                    self.patch(
+                        offset..offset,
+                        format!("{lines}{nl}{nl}", nl = self.newline),
+                    );
+                } else {
+                    let offset = self.source.len();
+                    let separator = if self.source.is_empty() || self.source.ends_with('\n') {
+                        ""
+                    } else {
+                        self.newline
+                    };
+                    self.patch(
+                        offset..offset,
+                        format!("{separator}{lines}{}", self.newline),
+                    );
+                }
+            }
+            TABLE_HEADER | TABLE_ARRAY_HEADER => {
+                let last_entry = syntax
+                    .siblings(Direction::Next)
+                    .skip(1)
+                    .take_while(|n| !is_header(n))
+                    .filter(|n| n.kind() == ENTRY)
+                    .last()
+                    .unwrap_or(syntax);
+
+                let offset = self.line_end(last_entry.text_range());
+                self.patch(offset..offset, format!("{}{lines}", self.newline));
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Insert a table or an array of tables at the end of our document.
+    fn insert_section(&mut self, root: &Node, keys: &Keys, node: &Node) -> bool {
+        // Headers always refer to the last item of arrays of tables,
+        // so we can only append to those.
+        let mut current = root.clone();
+        for (i, key) in keys.iter().enumerate() {
+            if let (KeyOrIndex::Index(idx), Node::Array(arr)) = (key, &current) {
+                let len = arr.items().read().len();
+                let expected_len = if i + 1 == keys.len() { *idx } else { idx + 1 };
+
+                if len != expected_len {
+                    return false;
+                }
+            }
+
+            current = current.get(key);
+        }
+
+        let header_keys = Keys::new(keys.iter().filter(|k| k.is_key()).cloned());
+
+        let mut text = String::new();
+        let res = if let Some(KeyOrIndex::Index(_)) = keys.iter().last() {
+            text += "[[";
+            text += header_keys.dotted();
+            text += "]]\n";
+            node.to_toml_impl(&mut text, header_keys, false, true, false)
+        } else {
+            node.to_toml_impl(&mut text, header_keys, false, false, false)
+        };
+
+        if res.is_err() {
+            return false;
+        }
+
+        let mut separator = String::new();
+        if !self.source.is_empty() {
+            if !self.source.ends_with('\n') {
+                separator += self.newline;
+            }
+            separator += self.newline;
+        }
+
+        let offset = self.source.len();
+        self.patch(
+            offset..offset,
+            separator + &text.replace('\n', self.newline),
+        );
+
+        true
+    }
+
+    /// The range of all the lines the given range spans,
+    /// if there is nothing else on those lines other than
+    /// whitespace and comments.
+    fn line_range(&self, range: TextRange) -> Range<usize> {
+        let range = std_range(range);
+
+        let line_start = self.source[..range.start]
+            .rfind('\n')
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+
+        if !self.source[line_start..range.start].trim().is_empty() {
+            return range;
+        }
+
+        let line_end = self.source[range.end..]
+            .find('\n')
+            .map(|idx| range.end + idx + 1)
+            .unwrap_or_else(|| self.source.len());
+
+        let rest = self.source[range.end..line_end].trim();
+
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return range;
+        }
+
+        line_start..line_end
+    }
+
+    /// The offset right before the line break of the last line in the range.
+    fn line_end(&self, range: TextRange) -> usize {
+        let end = std_range(range).end;
+        self.source[end..]
+            .find(self.newline)
+            .map(|idx| end + idx)
+            .unwrap_or_else(|| self.source.len())
+    }
+}
+
+impl core::fmt::Display for Merge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut patches = self.patches.iter().collect::<Vec<_>>();
+        patches.sort_by_key(|(range, _)| (range.start, range.end));
+
+        let mut offset = 0;
+        for (range, text) in patches {
+            if range.start < offset {
+                continue;
+            }
+
+            f.write_str(&self.source[offset..range.start])?;
+            f.write_str(text)?;
+            offset = range.end;
+        }
+
+        f.write_str(&self.source[offset..])
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("only root nodes can be merged")]
+    RootNodeExpected,
+}
+
+/// Merge the changes made in two documents derived from the same base document.
+///
+/// The documents are compared with [`diff`](super::diff()), changes made to
+/// different keys are merged together, while different changes made
+/// to the same key (or one of its parents or children) are reported as conflicts.
+///
+/// The merge preserves the formatting and comments of our document,
+/// new values are copied from their document whenever possible.
+pub fn merge3(base: &Node, ours: &Node, theirs: &Node) -> Result<Merge, Error> {
+    for root in [base, ours, theirs] {
+        if !matches!(root.syntax().map(|s| s.kind()), Some(ROOT)) {
+            return Err(Error::RootNodeExpected);
+        }
+    }
+
+    let source = ours.syntax().unwrap().to_string();
+
+    let mut merge = Merge {
+        newline: if source.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        },
+        source,
+        patches: Vec::new(),
+        conflicts: Vec::new(),
+    };
+
+    let our_changes = diff(base, ours).collect::<Vec<_>>();
+
+    // Additions are done last so that they are not
+    // placed in ranges that are removed.
+    let mut additions = Vec::new();
+
+    for change in diff(base, theirs) {
+        let keys = change.keys();
+
+        let mut overlapping = our_changes
+            .iter()
+            .filter(|c| c.keys().contains(keys) || keys.contains(c.keys()));
+
+        if let Some(ours_change) = overlapping.next() {
+            let same_change = ours_change.keys().len() == keys.len()
+                && match (ours_change.new_node(), change.new_node()) {
+                    (Some(a), Some(b)) => value_eq(a, b),
+                    (None, None) => true,
+                    _ => false,
+                };
+
+            if !same_change || overlapping.next().is_some() {
+                merge.conflicts.push(Conflict {
+                    keys: keys.clone(),
+                    base: base.path(keys),
+                    ours: ours.path(keys),
+                    theirs: theirs.path(keys),
+                });
+            }
+
+            continue;
+        }
+
+        match change {
+            Change::Added { keys, node } => additions.push((keys, node)),
+            Change::Removed { keys, .. } => {
+                if let Some(node) = ours.path(&keys) {
+                    merge.remove(&node);
+                }
+            }
+            Change::Modified { keys, new, .. } => match ours.path(&keys) {
+                Some(node) if !is_container(&node) && !is_container(&new) => {
+                    merge.replace(&node, &new);
+                }
+                Some(node) => {
+                    merge.remove(&node);
+                    additions.push((keys, new));
+                }
+                None => additions.push((keys, new)),
+            },
+        }
+    }
+
+    for (keys, node) in additions {
+        if !merge.insert(ours, &keys, &node) {
+            merge.conflicts.push(Conflict {
+                base: base.path(&keys),
+                ours: ours.path(&keys),
+                theirs: Some(node),
+                keys,
+            });
+        }
+    }
+
+    Ok(merge)
+}
+
+/// A table that was defined with dotted keys.
+fn is_dotted(node: &Node) -> bool {
+    match node {
+        Node::Table(t) => t.kind() == TableKind::Pseudo && !t.inner.header,
+        _ => false,
+    }
+}
+
+fn is_header(node: &SyntaxNode) -> bool {
+    matches!(node.kind(), TABLE_HEADER | TABLE_ARRAY_HEADER)
+}
+
+fn entry_lines(keys: Keys, node: &Node, lines: &mut Vec<String>) {
+    match node {
+        Node::Table(t) if is_dotted(node) => {
+            for (key, entry) in t.entries().read().iter() {
+                entry_lines(keys.join(key.clone()), entry, lines);
+            }
+        }
+        _ => lines.push(format!("{} = {}", keys.dotted(), value_text(node))),
+    }
+}
+
+/// The original representation of a value if available.
+fn value_text(node: &Node) -> String {
+    node.syntax()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| node.to_toml(true, false))
+}
+
+fn std_range(range: TextRange) -> Range<usize> {
+    let start: usize = u32::from(range.start()) as usize;
+    let end: usize = u32::from(range.end()) as usize;
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge3;
+    use crate::parser::parse;
+
+    #[test]
+    fn merge_disjoint_changes() {
+        let base = r#"# Package
+[package]
+name = "foo"
+version = "0.1.0"
+
+[dependencies]
+serde = "1"
+"#;
+
+        let ours = r#"# Package
+[package]
+name = "foo"
+version = "0.2.0" # bumped
+
+[dependencies]
+serde = "1"
+"#;
+
+        let theirs = r#"# Package
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+tokio = "1"
+"#;
+
+        let expected = r#"# Package
+[package]
+name = "foo"
+version = "0.2.0" # bumped
+edition = "2021"
+
+[dependencies]
+tokio = "1"
+"#;
+
+        let merge = merge3(
+            &parse(base).into_dom(),
+            &parse(ours).into_dom(),
+            &parse(theirs).into_dom(),
+        )
+        .unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(expected, merge.to_string());
+    }
+
+    #[test]
+    fn merge_new_tables() {
+        let base = r#"a = 1
+"#;
+
+        let ours = r#"a = 1
+b.c = 2
+"#;
+
+        let theirs = r#"a = 1
+
+[table]
+d = 3
+"#;
+
+        let expected = r#"a = 1
+b.c = 2
+
+[table]
+d = 3
+"#;
+
+        let merge = merge3(
+            &parse(base).into_dom(),
+            &parse(ours).into_dom(),
+            &parse(theirs).into_dom(),
+        )
+        .unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(expected, merge.to_string());
+    }
+
+    #[test]
+    fn merge_conflicts() {
+        let base = r#"
+[package]
+version = "0.1.0"
+"#;
+
+        let ours = r#"
+[package]
+version = "0.2.0"
+"#;
+
+        let theirs = r#"
+[package]
+version = "0.3.0"
+"#;
+
+        let merge = merge3(
+            &parse(base).into_dom(),
+            &parse(ours).into_dom(),
+            &parse(theirs).into_dom(),
+        )
+        .unwrap();
+
+        assert_eq!(merge.conflicts().len(), 1);
+
+        let conflict = &merge.conflicts()[0];
+        assert_eq!(conflict.keys.dotted(), "package.version");
+        assert_eq!(
+            &ours[conflict.ours_range().map(super::std_range).unwrap()],
+            r#""0.2.0""#
+        );
+        assert_eq!(
+            &theirs[conflict.theirs_range().map(super::std_range).unwrap()],
+            r#""0.3.0""#
+        );
+        assert_eq!(ours, merge.to_string());
+    }
+
+    #[test]
+    fn merge_same_change() {
+        let base = "a = 1\n";
+        let ours = "a = 2\n";
+        let theirs = "a = 0x2\n";
+
+        let merge = merge3(
+            &parse(base).into_dom(),
+            &parse(ours).into_dom(),
+            &parse(theirs).into_dom(),
+        )
+        .unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(ours, merge.to_string());
+    }
+}
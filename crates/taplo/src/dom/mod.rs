@@ -12,17 +12,24 @@ mod serde;
 
 pub(crate) mod from_syntax;
 
+pub mod coerce;
+pub mod diff;
 pub mod error;
 pub mod index;
+pub mod merge;
 pub mod node;
 pub mod rewrite;
+pub mod strings;
 mod to_toml;
 
+pub use diff::diff;
 pub use error::Error;
 pub use from_syntax::FromSyntax;
 use itertools::Itertools;
+pub use merge::merge3;
 pub use node::Node;
 use once_cell::unsync::OnceCell;
+pub use rewrite::replace_values;
 use rowan::TextRange;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -14,6 +14,7 @@ use rowan::TextRange;
 use super::{
     error::{Error, QueryError},
     index::Index,
+    strings::StringValue,
     Comment, FromSyntax, KeyOrIndex, Keys,
 };
 
@@ -35,6 +36,7 @@ pub enum Node {
     Integer(Integer),
     Float(Float),
     Date(DateTime),
+    Unknown(Unknown),
     Invalid(Invalid),
 }
 
@@ -49,6 +51,7 @@ impl DomNode for Node {
             Node::Integer(n) => n.syntax(),
             Node::Float(n) => n.syntax(),
             Node::Date(n) => n.syntax(),
+            Node::Unknown(n) => n.syntax(),
             Node::Invalid(n) => n.syntax(),
         }
     }
@@ -62,6 +65,7 @@ impl DomNode for Node {
             Node::Integer(n) => n.errors(),
             Node::Float(n) => n.errors(),
             Node::Date(n) => n.errors(),
+            Node::Unknown(n) => n.errors(),
             Node::Invalid(n) => n.errors(),
         }
     }
@@ -75,6 +79,7 @@ impl DomNode for Node {
             Node::Integer(n) => n.validate_node(),
             Node::Float(n) => n.validate_node(),
             Node::Date(n) => n.validate_node(),
+            Node::Unknown(n) => n.validate_node(),
             Node::Invalid(n) => n.validate_node(),
         }
     }
@@ -283,12 +288,19 @@ impl Node {
             Node::Integer(v) => ranges.push(v.syntax().map(|s| s.text_range()).unwrap_or_default()),
             Node::Float(v) => ranges.push(v.syntax().map(|s| s.text_range()).unwrap_or_default()),
             Node::Date(v) => ranges.push(v.syntax().map(|s| s.text_range()).unwrap_or_default()),
+            Node::Unknown(v) => ranges.push(v.syntax().map(|s| s.text_range()).unwrap_or_default()),
             Node::Invalid(v) => ranges.push(v.syntax().map(|s| s.text_range()).unwrap_or_default()),
         }
 
         ranges.into_iter()
     }
 
+    /// All string values in the tree in the order they appear in the document
+    /// with their positions, see [`StringValue`].
+    pub fn strings(&self) -> impl Iterator<Item = StringValue> {
+        super::strings::strings(self)
+    }
+
     /// All the comments in the tree, including header comments returned from [`Self::header_comments`].
     pub fn comments(&self) -> impl Iterator<Item = Comment> {
         if let Some(syntax) = self.syntax().cloned().and_then(|s| s.into_node()) {
@@ -417,6 +429,11 @@ impl Node {
                     errors.extend(errs.read().as_ref().iter().cloned())
                 }
             }
+            Node::Unknown(v) => {
+                if let Err(errs) = v.validate_node() {
+                    errors.extend(errs.read().as_ref().iter().cloned())
+                }
+            }
             Node::Invalid(v) => {
                 if let Err(errs) = v.validate_node() {
                     errors.extend(errs.read().as_ref().iter().cloned())
@@ -476,6 +493,13 @@ impl Node {
         matches!(self, Self::Date(..))
     }
 
+    /// Returns `true` if the node is [`Unknown`].
+    ///
+    /// [`Unknown`]: Node::Unknown
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(..))
+    }
+
     /// Returns `true` if the node is [`Invalid`].
     ///
     /// [`Invalid`]: Node::Invalid
@@ -539,6 +563,14 @@ impl Node {
         }
     }
 
+    pub fn as_unknown(&self) -> Option<&Unknown> {
+        if let Self::Unknown(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
     pub fn as_invalid(&self) -> Option<&Invalid> {
         if let Self::Invalid(v) = self {
             Some(v)
@@ -603,6 +635,14 @@ impl Node {
         }
     }
 
+    pub fn try_into_unknown(self) -> Result<Unknown, Self> {
+        if let Self::Unknown(v) = self {
+            Ok(v)
+        } else {
+            Err(self)
+        }
+    }
+
     pub fn try_into_invalid(self) -> Result<Invalid, Self> {
         if let Self::Invalid(v) = self {
             Ok(v)
@@ -654,6 +694,12 @@ impl From<Table> for Node {
     }
 }
 
+impl From<Unknown> for Node {
+    fn from(v: Unknown) -> Self {
+        Self::Unknown(v)
+    }
+}
+
 impl From<Invalid> for Node {
     fn from(v: Invalid) -> Self {
         Self::Invalid(v)
@@ -1,12 +1,12 @@
 use super::{DomNode, Node};
 use crate::{
     dom::{error::Error, Entries, KeyOrIndex, Keys},
-    syntax::{SyntaxElement, SyntaxKind},
-    util::{shared::Shared, unescape},
+    syntax::{SyntaxElement, SyntaxKind, SyntaxToken},
+    util::{check_escape_ranges, shared::Shared, unescape},
 };
 use logos::Lexer;
 use once_cell::unsync::OnceCell;
-use rowan::{NodeOrToken, TextRange};
+use rowan::{NodeOrToken, TextRange, TextSize};
 use std::{fmt::Write, iter::once, sync::Arc};
 use time::macros::format_description;
 
@@ -83,7 +83,9 @@ impl Table {
         self.inner.kind
     }
 
-    /// Add an entry and also collect errors on conflicts.
+    /// Add an entry, merging pseudo-tables of dotted keys.
+    ///
+    /// Conflicts are not checked here, see [`KeyIndex`](crate::dom::index::KeyIndex).
     pub(crate) fn add_entry(&self, key: Key, node: Node) {
         self.inner.entries.update(|entries| {
             if let Some((existing_key, value)) = entries.lookup.get_key_value(&key) {
@@ -105,13 +107,6 @@ impl Table {
                         return;
                     }
                 }
-
-                self.inner.errors.update(|errors| {
-                    errors.push(Error::ConflictingKeys {
-                        key: key.clone(),
-                        other: existing_key.clone(),
-                    })
-                });
             }
 
             entries.add(key, node);
@@ -208,9 +203,7 @@ impl Key {
                             Ok(s) => s,
                             Err(_) => {
                                 self.inner.errors.update(|errors| {
-                                    errors.push(Error::InvalidEscapeSequence {
-                                        string: s.clone().into(),
-                                    })
+                                    errors.extend(invalid_escape_errors(s, string))
                                 });
                                 String::new()
                             }
@@ -404,63 +397,66 @@ impl Str {
     /// An unescaped value of the string.
     pub fn value(&self) -> &str {
         self.inner.value.get_or_init(|| {
-            self.inner
-                .syntax
-                .as_ref()
-                .map(|s| match self.inner.repr {
-                    StrRepr::Basic => {
-                        let string = s.as_token().unwrap().text();
-                        let string = string.strip_prefix('"').unwrap_or(string);
-                        let string = string.strip_suffix('"').unwrap_or(string);
-                        match unescape(string) {
-                            Ok(s) => s,
-                            Err(_) => {
-                                self.inner.errors.update(|errors| {
-                                    errors.push(Error::InvalidEscapeSequence { string: s.clone() })
-                                });
-                                String::new()
-                            }
-                        }
-                    }
-                    StrRepr::Literal => {
-                        let string = s.as_token().unwrap().text();
-                        let string = string.strip_prefix('\'').unwrap_or(string);
-                        let string = string.strip_suffix('\'').unwrap_or(string);
-                        string.to_string()
-                    }
-                    StrRepr::MultiLine => {
-                        let string = s.as_token().unwrap().text();
-                        let string = string.strip_prefix(r#"""""#).unwrap_or(string);
-                        let string = match string.strip_prefix("\r\n") {
-                            Some(s) => s,
-                            None => string.strip_prefix('\n').unwrap_or(string),
-                        };
-                        let string = string.strip_suffix(r#"""""#).unwrap_or(string);
-                        match unescape(string) {
-                            Ok(s) => s,
-                            Err(_) => {
-                                self.inner.errors.update(|errors| {
-                                    errors.push(Error::InvalidEscapeSequence { string: s.clone() })
-                                });
-                                String::new()
-                            }
-                        }
-                    }
-                    StrRepr::MultiLineLiteral => {
-                        let string = s.as_token().unwrap().text();
-                        let string = string.strip_prefix(r#"'''"#).unwrap_or(string);
-                        let string = match string.strip_prefix("\r\n") {
-                            Some(s) => s,
-                            None => string.strip_prefix('\n').unwrap_or(string),
-                        };
-                        let string = string.strip_suffix(r#"'''"#).unwrap_or(string);
-                        string.to_string()
+            let content = self.content();
+
+            if matches!(
+                self.inner.repr,
+                StrRepr::Literal | StrRepr::MultiLineLiteral
+            ) {
+                return content.to_string();
+            }
+
+            match unescape(content) {
+                Ok(s) => s,
+                Err(_) => {
+                    if let Some(token) = self.inner.syntax.as_ref().and_then(NodeOrToken::as_token)
+                    {
+                        self.inner
+                            .errors
+                            .update(|errors| errors.extend(invalid_escape_errors(token, content)));
                     }
-                })
-                .unwrap_or_default()
+                    String::new()
+                }
+            }
         })
     }
 
+    /// The string exactly as it is written in the document between the quotes.
+    ///
+    /// Escape sequences are not unescaped, and the newline
+    /// after the opening quotes of multi-line strings is included.
+    pub fn raw(&self) -> &str {
+        let text = self
+            .inner
+            .syntax
+            .as_ref()
+            .and_then(NodeOrToken::as_token)
+            .map(|token| token.text())
+            .unwrap_or_default();
+
+        let quotes = self.inner.repr.quotes();
+        let text = text.strip_prefix(quotes).unwrap_or(text);
+        text.strip_suffix(quotes).unwrap_or(text)
+    }
+
+    /// The raw string without the trimmed newline of multi-line strings.
+    pub(crate) fn content(&self) -> &str {
+        let raw = self.raw();
+
+        match self.inner.repr {
+            StrRepr::MultiLine | StrRepr::MultiLineLiteral => raw
+                .strip_prefix("\r\n")
+                .or_else(|| raw.strip_prefix('\n'))
+                .unwrap_or(raw),
+            StrRepr::Basic | StrRepr::Literal => raw,
+        }
+    }
+
+    /// The kind of the string in the document.
+    pub fn repr(&self) -> StrRepr {
+        self.inner.repr
+    }
+
     fn validate_impl(&self) -> Result<(), &Shared<Vec<Error>>> {
         let _ = self.value();
         if self.errors().read().as_ref().is_empty() {
@@ -471,7 +467,7 @@ impl Str {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum StrRepr {
     Basic,
     MultiLine,
@@ -479,6 +475,38 @@ pub enum StrRepr {
     MultiLineLiteral,
 }
 
+impl StrRepr {
+    /// The quotes around strings of this kind.
+    pub fn quotes(self) -> &'static str {
+        match self {
+            StrRepr::Basic => r#"""#,
+            StrRepr::MultiLine => r#"""""#,
+            StrRepr::Literal => "'",
+            StrRepr::MultiLineLiteral => "'''",
+        }
+    }
+}
+
+/// An error for every invalid escape sequence in the given part of a string token.
+fn invalid_escape_errors(token: &SyntaxToken, content: &str) -> Vec<Error> {
+    let text = token.text();
+    let offset = content.as_ptr() as usize - text.as_ptr() as usize;
+    let start = token.text_range().start() + TextSize::from(offset as u32);
+
+    check_escape_ranges(content)
+        .err()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|range| Error::InvalidEscapeSequence {
+            string: token.clone().into(),
+            range: TextRange::new(
+                start + TextSize::from(range.start as u32),
+                start + TextSize::from(range.end as u32),
+            ),
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub(crate) struct IntegerInner {
     pub(crate) errors: Shared<Vec<Error>>,
@@ -755,6 +783,36 @@ impl core::fmt::Display for DateTimeValue {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct UnknownInner {
+    pub(crate) errors: Shared<Vec<Error>>,
+    pub(crate) syntax: Option<SyntaxElement>,
+}
+
+wrap_node! {
+    /// A construct that could not be parsed but was preserved,
+    /// e.g. a value of a newer TOML version.
+    ///
+    /// See [`parse_preserving_unknown`](crate::parser::parse_preserving_unknown).
+    #[derive(Debug, Clone)]
+    pub struct Unknown { inner: UnknownInner }
+}
+
+impl Unknown {
+    /// The text of the construct as it is in the document.
+    pub fn text(&self) -> String {
+        self.syntax().map(ToString::to_string).unwrap_or_default()
+    }
+
+    fn validate_impl(&self) -> Result<(), &Shared<Vec<Error>>> {
+        if self.errors().read().as_ref().is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors())
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct InvalidInner {
     pub(crate) errors: Shared<Vec<Error>>,
@@ -1,5 +1,6 @@
 use super::{
-    node::{DomNode, Node},
+    error::QueryError,
+    node::{ArrayKind, DomNode, Node, TableKind},
     Keys,
 };
 use crate::{dom, syntax::SyntaxKind};
@@ -48,6 +49,30 @@ impl Rewrite {
                     }
                 }
             }
+            Patch::ReplaceValue { keys, to } => {
+                let range = self
+                    .root
+                    .path(&keys)
+                    .and_then(|node| node.syntax().map(|s| s.text_range()))
+                    .ok_or(dom::Error::Query(QueryError::NotFound))?;
+
+                // Nested values are only replaced once,
+                // whichever of them was replaced first is kept.
+                if self
+                    .patches
+                    .iter()
+                    .any(|p| p.range.contains_range(range) || range.contains_range(p.range))
+                {
+                    return Ok(self);
+                }
+
+                self.check_overlap(range)?;
+
+                self.patches.push(PendingPatch {
+                    range,
+                    kind: PendingPatchKind::Replace(to),
+                });
+            }
         }
 
         self.patches
@@ -82,6 +107,49 @@ impl Rewrite {
             to: to.into(),
         })
     }
+
+    /// Replace the value at the given path with the given TOML text.
+    ///
+    /// The value is skipped if it is inside or contains
+    /// a value that was already replaced.
+    pub fn replace_value(&mut self, keys: &Keys, to: &str) -> Result<&mut Self, Error> {
+        self.add(Patch::ReplaceValue {
+            keys: keys.clone(),
+            to: to.into(),
+        })
+    }
+}
+
+/// Replace the values that match the given keys with the TOML text returned by `replacer`,
+/// the values for which it returns [`None`] are kept.
+///
+/// The keys can contain glob patterns like in [`Node::find_all_matches`].
+/// Only values after `=` and their items can be replaced, tables with headers,
+/// arrays of tables and tables of dotted keys are skipped.
+pub fn replace_values(
+    root: Node,
+    keys: Keys,
+    mut replacer: impl FnMut(&Keys, &Node) -> Option<String>,
+) -> Result<Rewrite, Error> {
+    let mut rewrite = Rewrite::new(root)?;
+
+    for (keys, node) in rewrite.root.find_all_matches(keys, false)? {
+        let replaceable = match &node {
+            Node::Table(t) => t.kind() == TableKind::Inline,
+            Node::Array(arr) => arr.kind() == ArrayKind::Inline,
+            _ => true,
+        };
+
+        if !replaceable {
+            continue;
+        }
+
+        if let Some(to) = replacer(&keys, &node) {
+            rewrite.replace_value(&keys, &to)?;
+        }
+    }
+
+    Ok(rewrite)
 }
 
 impl core::fmt::Display for Rewrite {
@@ -103,6 +171,7 @@ impl core::fmt::Display for Rewrite {
 #[derive(Debug)]
 pub enum Patch {
     RenameKeys { key: Arc<str>, to: Arc<str> },
+    ReplaceValue { keys: Keys, to: Arc<str> },
 }
 
 #[derive(Debug)]
@@ -131,8 +200,11 @@ pub enum Error {
 
 #[cfg(test)]
 mod tests {
-    use super::Rewrite;
-    use crate::parser::parse;
+    use super::{replace_values, Rewrite};
+    use crate::{
+        dom::{node::Key, Keys},
+        parser::parse,
+    };
 
     #[test]
     fn rename_keys() {
@@ -191,6 +263,98 @@ mod tests {
 
         assert_eq!(expected_toml, patches.to_string());
     }
+
+    #[test]
+    fn replace_value() {
+        let toml = r#"
+[server]
+port = "8080" # comment
+hosts = [{ name = "a", enabled = "true" }]
+"#;
+
+        let expected_toml = r#"
+[server]
+port = 8080 # comment
+hosts = [{ name = "a", enabled = true }]
+"#;
+
+        let root = parse(toml).into_dom();
+
+        let mut patches = Rewrite::new(root).unwrap();
+
+        let port = "server.port".parse::<Keys>().unwrap();
+        let enabled = "server.hosts"
+            .parse::<Keys>()
+            .unwrap()
+            .join(0_usize)
+            .join(Key::new("enabled"));
+
+        patches.replace_value(&port, "8080").unwrap();
+        patches.replace_value(&enabled, "true").unwrap();
+
+        assert_eq!(expected_toml, patches.to_string());
+    }
+
+    #[test]
+    fn replace_nested_values() {
+        let toml = r#"
+server = { hosts = ["a", "b"] }
+"#;
+
+        let server = "server".parse::<Keys>().unwrap();
+        let host = "server.hosts".parse::<Keys>().unwrap().join(0_usize);
+
+        let mut patches = Rewrite::new(parse(toml).into_dom()).unwrap();
+        patches.replace_value(&server, r#"{ hosts = [] }"#).unwrap();
+        patches.replace_value(&host, r#""c""#).unwrap();
+
+        assert_eq!("\nserver = { hosts = [] }\n", patches.to_string());
+
+        let mut patches = Rewrite::new(parse(toml).into_dom()).unwrap();
+        patches.replace_value(&host, r#""c""#).unwrap();
+        patches.replace_value(&server, r#"{ hosts = [] }"#).unwrap();
+
+        assert_eq!(
+            "\nserver = { hosts = [\"c\", \"b\"] }\n",
+            patches.to_string()
+        );
+    }
+
+    #[test]
+    fn replace_values_matching_keys() {
+        let toml = r#"
+[dependencies]
+a = { git = "https://example.com/a", branch = "main" }
+b = { git = 'https://example.com/b' }
+c = "1.0"
+
+[dev-dependencies]
+d = { git = "https://example.com/d" }
+"#;
+
+        let expected_toml = r#"
+[dependencies]
+a = { git = "https://example.org/a", branch = "main" }
+b = { git = "https://example.org/b" }
+c = "1.0"
+
+[dev-dependencies]
+d = { git = "https://example.com/d" }
+"#;
+
+        let root = parse(toml).into_dom();
+
+        let patches = replace_values(root, "dependencies.*.git".parse().unwrap(), |_, node| {
+            let url = node.as_str()?.value();
+            Some(format!(
+                r#""{}""#,
+                url.replace("example.com", "example.org")
+            ))
+        })
+        .unwrap();
+
+        assert_eq!(expected_toml, patches.to_string());
+    }
 }
 
 fn std_range(range: TextRange) -> Range<usize> {
@@ -20,7 +20,7 @@ impl Serialize for Node {
                 let mut map = ser.serialize_map(Some(entries.all.len()))?;
 
                 for (key, entry) in entries.all.iter() {
-                    if !entry.is_invalid() {
+                    if !entry.is_invalid() && !entry.is_unknown() {
                         map.serialize_entry(key.value(), entry)?;
                     }
                 }
@@ -31,7 +31,7 @@ impl Serialize for Node {
                 let items = arr.inner.items.read();
                 let mut seq = ser.serialize_seq(Some(items.len()))?;
                 for item in &**items {
-                    if !item.is_invalid() {
+                    if !item.is_invalid() && !item.is_unknown() {
                         seq.serialize_element(item)?;
                     }
                 }
@@ -45,6 +45,7 @@ impl Serialize for Node {
             },
             Node::Float(v) => ser.serialize_f64(v.value()),
             Node::Date(date) => ser.serialize_str(&date.value().to_string()),
+            Node::Unknown(_) => Err(Error::custom("unknown node cannot be serialized")),
             Node::Invalid(_) => {
                 // Invalid nodes are simply skipped from the serialization.
                 Err(Error::custom("invalid node cannot be serialized"))
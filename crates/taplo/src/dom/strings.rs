@@ -0,0 +1,298 @@
+//! String values with their positions in the document.
+
+use super::{
+    node::{DomNode, Node, Str, StrRepr},
+    Keys,
+};
+use crate::util::{escaped_parts, EscapedPart};
+use rowan::{TextRange, TextSize};
+use std::ops::Range;
+
+/// A string value in the document.
+#[derive(Debug, Clone)]
+pub struct StringValue {
+    /// The path of the string.
+    pub keys: Keys,
+    /// The unescaped content of the string.
+    ///
+    /// Invalid escape sequences are kept as they are written.
+    pub content: String,
+    pub repr: StrRepr,
+    /// The range of the string in the document including the quotes.
+    pub range: TextRange,
+    /// Positions of the content in the document.
+    pub offsets: OffsetMap,
+}
+
+/// Maps offsets in the unescaped content of a string
+/// to positions in the document.
+#[derive(Debug, Clone, Default)]
+pub struct OffsetMap {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    /// The start of the segment in the content.
+    content: usize,
+    /// The range of the segment in the document.
+    source: TextRange,
+    /// Whether the segment is an escape sequence.
+    escape: bool,
+}
+
+impl OffsetMap {
+    /// The position in the document of the given byte offset in the content.
+    ///
+    /// Offsets inside the character of an escape sequence
+    /// are mapped to the start of the escape sequence.
+    #[must_use]
+    pub fn source_offset(&self, offset: usize) -> TextSize {
+        let idx = self
+            .segments
+            .partition_point(|s| s.content <= offset)
+            .saturating_sub(1);
+
+        match self.segments.get(idx) {
+            Some(s) if s.escape => {
+                if offset > s.content {
+                    s.source.end()
+                } else {
+                    s.source.start()
+                }
+            }
+            Some(s) => s.source.start() + TextSize::from((offset - s.content) as u32),
+            None => TextSize::default(),
+        }
+    }
+
+    /// The range in the document of the given range in the content.
+    ///
+    /// Escape sequences at the ends of the range are fully included.
+    #[must_use]
+    pub fn source_range(&self, range: Range<usize>) -> TextRange {
+        let start = self.source_offset(range.start);
+
+        let end = match range.end.checked_sub(1) {
+            Some(last) if range.end > range.start => {
+                let idx = self
+                    .segments
+                    .partition_point(|s| s.content <= last)
+                    .saturating_sub(1);
+
+                match self.segments.get(idx) {
+                    Some(s) if s.escape => s.source.end(),
+                    _ => self.source_offset(range.end),
+                }
+            }
+            _ => start,
+        };
+
+        TextRange::new(start, end.max(start))
+    }
+
+    fn push_verbatim(&mut self, content: usize, source: TextRange) {
+        if let Some(last) = self.segments.last_mut() {
+            if !last.escape && last.source.end() == source.start() {
+                last.source = last.source.cover(source);
+                return;
+            }
+        }
+
+        self.segments.push(Segment {
+            content,
+            source,
+            escape: false,
+        });
+    }
+}
+
+pub(super) fn strings(node: &Node) -> impl Iterator<Item = StringValue> {
+    let mut strings: Vec<_> = match node {
+        Node::Str(s) => string_value(Keys::empty(), s).into_iter().collect(),
+        _ => node
+            .flat_iter()
+            .filter_map(|(keys, node)| string_value(keys, node.as_str()?))
+            .collect(),
+    };
+
+    strings.sort_by_key(|s| s.range.start());
+    strings.into_iter()
+}
+
+fn string_value(keys: Keys, s: &Str) -> Option<StringValue> {
+    let syntax = s.syntax()?;
+    let token = syntax.as_token()?;
+    let text = token.text();
+    let range = token.text_range();
+    let repr = s.inner.repr;
+
+    let escapes = matches!(repr, StrRepr::Basic | StrRepr::MultiLine);
+    let raw = s.content();
+
+    // The content starts after the opening quotes
+    // and the newline that is trimmed after them in multi-line strings.
+    let quotes = repr.quotes().len();
+    let newline = match repr {
+        StrRepr::MultiLine | StrRepr::MultiLineLiteral => {
+            let after_quotes = text.get(quotes..).unwrap_or_default();
+            if after_quotes.starts_with("\r\n") {
+                2
+            } else if after_quotes.starts_with('\n') {
+                1
+            } else {
+                0
+            }
+        }
+        StrRepr::Basic | StrRepr::Literal => 0,
+    };
+
+    let raw_start = range.start() + TextSize::from((quotes + newline) as u32);
+    let source = |r: Range<usize>| {
+        TextRange::new(
+            raw_start + TextSize::from(r.start as u32),
+            raw_start + TextSize::from(r.end as u32),
+        )
+    };
+
+    let mut content = String::with_capacity(raw.len());
+    let mut offsets = OffsetMap::default();
+
+    if escapes {
+        for (r, part) in escaped_parts(raw) {
+            match part {
+                EscapedPart::Verbatim(text) | EscapedPart::Invalid(text) => {
+                    offsets.push_verbatim(content.len(), source(r));
+                    content += text;
+                }
+                EscapedPart::Escape(c) => {
+                    offsets.segments.push(Segment {
+                        content: content.len(),
+                        source: source(r),
+                        escape: true,
+                    });
+                    content.extend(c);
+                }
+            }
+        }
+    } else {
+        offsets.push_verbatim(0, source(0..raw.len()));
+        content += raw;
+    }
+
+    Some(StringValue {
+        keys,
+        content,
+        repr,
+        range,
+        offsets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dom::node::StrRepr, parser::parse, util::escape_for};
+
+    #[test]
+    fn string_values() {
+        let src = r#"
+b = 1
+a = "tab\there é!"
+
+[table]
+c = ['lit', { d = """
+multi \
+line""" }]
+e = "\x"
+"#;
+        let strings: Vec<_> = parse(src).into_dom().strings().collect();
+
+        let keys: Vec<_> = strings
+            .iter()
+            .map(|s| s.keys.dotted().to_string())
+            .collect();
+        assert_eq!(keys, ["a", "table.c.0", "table.c.1.d", "table.e"]);
+
+        let a = &strings[0];
+        assert_eq!(a.content, "tab\there é!");
+        assert_eq!(a.repr, StrRepr::Basic);
+        assert_eq!(&src[a.range], r#""tab\there é!""#);
+        assert_eq!(&src[a.offsets.source_range(0..3)], "tab");
+        assert_eq!(&src[a.offsets.source_range(3..4)], r"\t");
+        assert_eq!(&src[a.offsets.source_range(4..8)], "here");
+        assert_eq!(&src[a.offsets.source_range(9..12)], r"é!");
+
+        let lit = &strings[1];
+        assert_eq!(lit.content, "lit");
+        assert_eq!(&src[lit.offsets.source_range(0..3)], "lit");
+
+        let multi = &strings[2];
+        assert_eq!(multi.repr, StrRepr::MultiLine);
+        assert_eq!(multi.content, "multi line");
+        assert_eq!(&src[multi.offsets.source_range(0..5)], "multi");
+        assert_eq!(&src[multi.offsets.source_range(6..10)], "line");
+
+        let invalid = &strings[3];
+        assert_eq!(invalid.content, r"\x");
+        assert_eq!(&src[invalid.offsets.source_range(0..2)], r"\x");
+    }
+
+    #[test]
+    fn escape_round_trip() {
+        let values = [
+            "plain",
+            "quote \" and backslash \\",
+            "it's",
+            "three \"\"\" quotes",
+            "\nleading newline",
+            "bell \u{7} and tab \t",
+            "ends with quotes ''",
+        ];
+        let reprs = [
+            StrRepr::Basic,
+            StrRepr::MultiLine,
+            StrRepr::Literal,
+            StrRepr::MultiLineLiteral,
+        ];
+
+        for value in values {
+            for repr in reprs {
+                let escaped = match escape_for(value, repr) {
+                    Some(escaped) => escaped,
+                    None => continue,
+                };
+
+                let src = format!("a = {q}{escaped}{q}", q = repr.quotes());
+                let parse = parse(&src);
+                assert!(parse.errors.is_empty(), "{src}: {:?}", parse.errors);
+
+                let dom = parse.into_dom();
+                let a = dom.get("a");
+                let s = a.as_str().unwrap();
+                assert_eq!(s.value(), value, "{src}");
+                assert_eq!(s.repr(), repr);
+                assert_eq!(s.raw(), escaped);
+            }
+        }
+
+        assert!(escape_for("it's", StrRepr::Literal).is_none());
+        assert!(escape_for("a\nb", StrRepr::Literal).is_none());
+        assert!(escape_for("a'''b", StrRepr::MultiLineLiteral).is_none());
+    }
+
+    #[test]
+    fn invalid_escape_ranges() {
+        let src = r#"a = "ok \uZZZZ and \x""#;
+
+        let parse = parse(src);
+        let ranges: Vec<_> = parse.errors.iter().map(|e| &src[e.range]).collect();
+        assert_eq!(ranges, [r"\uZZZZ", r"\x"]);
+
+        let errors: Vec<_> = parse.into_dom().validate().unwrap_err().collect();
+        let ranges: Vec<_> = errors
+            .iter()
+            .map(|e| &src[e.text_range().unwrap()])
+            .collect();
+        assert_eq!(ranges, [r"\uZZZZ", r"\x"]);
+    }
+}
@@ -23,7 +23,7 @@ impl Node {
         self.to_toml_impl(f, Keys::empty(), inline, false, prefer_single_quote)
     }
 
-    fn to_toml_impl(
+    pub(crate) fn to_toml_impl(
         &self,
         f: &mut impl Write,
         parent_keys: Keys,
@@ -31,8 +31,12 @@ impl Node {
         no_header: bool,
         prefer_single_quote: bool,
     ) -> core::fmt::Result {
-        if let Node::Bool(_) | Node::Str(_) | Node::Integer(_) | Node::Float(_) | Node::Date(_) =
-            self
+        if let Node::Bool(_)
+        | Node::Str(_)
+        | Node::Integer(_)
+        | Node::Float(_)
+        | Node::Date(_)
+        | Node::Unknown(_) = self
         {
             if !parent_keys.is_empty() {
                 f.write_str(parent_keys.dotted())?;
@@ -177,7 +181,7 @@ impl Node {
                 write!(f, "{}", float.value())?;
             }
             Node::Date(d) => write!(f, "{}", d.value())?,
-            Node::Invalid(_) => {}
+            Node::Unknown(_) | Node::Invalid(_) => {}
         }
 
         Ok(())
@@ -0,0 +1,78 @@
+//! Layout decisions of the formatter.
+
+use super::Options;
+use std::fmt::Debug;
+
+/// Decides how values are laid out, the rest of the formatting
+/// is done by the formatter the same way regardless of the layout.
+///
+/// The decisions are based on statistics collected by the formatter,
+/// [`DefaultLayout`] follows the formatter [`Options`].
+pub trait Layout: Debug {
+    /// Whether the value of an entry is formatted again with its arrays expanded.
+    fn expand_entry(&self, stats: &EntryStats, options: &Options) -> bool;
+
+    /// Whether an array is formatted on multiple lines.
+    fn multiline_array(&self, stats: &ArrayStats, options: &Options) -> bool;
+
+    /// Whether the arrays in an inline table are expanded
+    /// when the entry of the inline table is expanded.
+    fn expand_inline_table(&self, stats: &InlineTableStats, options: &Options) -> bool;
+}
+
+/// Statistics of a formatted entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryStats {
+    pub indent_level: usize,
+    /// The width of the longest line of the entry in characters,
+    /// including the indentation, the key and the trailing comment.
+    pub width: usize,
+    pub line_count: usize,
+}
+
+/// Statistics of an array before it is formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayStats {
+    pub indent_level: usize,
+    pub item_count: usize,
+    /// Whether the array spans multiple lines in the document.
+    pub multiline: bool,
+    /// Whether the array contains comments.
+    pub comments: bool,
+    /// Whether the entry of the array is expanded, see [`Layout::expand_entry`].
+    pub expanded: bool,
+}
+
+/// Statistics of an inline table before it is formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InlineTableStats {
+    pub indent_level: usize,
+    pub entry_count: usize,
+}
+
+/// The layout based on the formatter options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultLayout;
+
+impl Layout for DefaultLayout {
+    /// Entries that are longer than the column width are expanded.
+    fn expand_entry(&self, stats: &EntryStats, options: &Options) -> bool {
+        options.array_auto_expand && stats.width > options.column_width
+    }
+
+    /// Arrays without comments are collapsed unless their entries are expanded,
+    /// otherwise they keep their lines.
+    fn multiline_array(&self, stats: &ArrayStats, options: &Options) -> bool {
+        if stats.expanded {
+            true
+        } else if !stats.comments && options.array_auto_collapse {
+            false
+        } else {
+            stats.multiline
+        }
+    }
+
+    fn expand_inline_table(&self, _stats: &InlineTableStats, options: &Options) -> bool {
+        options.inline_table_expand
+    }
+}
@@ -12,6 +12,7 @@ use rowan::{GreenNode, NodeOrToken, TextRange};
 use std::{
     cmp,
     iter::{repeat, FromIterator},
+    mem,
     ops::Range,
     rc::Rc,
 };
@@ -25,6 +26,14 @@ use schemars::JsonSchema;
 #[macro_use]
 mod macros;
 
+mod on_type;
+pub use on_type::{format_on_type, Edit, TRIGGER_CHARACTERS};
+
+mod reflow;
+
+mod layout;
+pub use layout::{ArrayStats, DefaultLayout, EntryStats, InlineTableStats, Layout};
+
 #[derive(Debug, Clone, Default)]
 /// Scoped formatter options based on text ranges.
 pub struct ScopedOptions(Vec<(TextRange, OptionsIncomplete)>);
@@ -46,6 +55,13 @@ create_options!(
         /// or blank lines between them are not aligned.
         pub align_entries: bool,
 
+        /// Align entries and comments across blank lines within a table
+        /// instead of only aligning consecutive lines.
+        ///
+        /// Entries that have table headers or comments between them are still
+        /// aligned separately.
+        pub align_across_blank_lines: bool,
+
         /// Align consecutive comments after entries and items vertically.
         ///
         /// This applies to comments that are after entries or array items.
@@ -89,6 +105,13 @@ create_options!(
         /// This is best-effort and might not be accurate.
         pub column_width: usize,
 
+        /// Reflow blocks of comments that are longer than `column_width`.
+        ///
+        /// Lists and indentation are preserved, directives, fenced
+        /// code blocks, headings, tables and quotes are not changed.
+        /// Comments in arrays, inline tables and after entries are not reflowed.
+        pub reflow_comments: bool,
+
         /// Indent subtables if they come in order.
         pub indent_tables: bool,
 
@@ -148,6 +171,7 @@ impl Default for Options {
     fn default() -> Self {
         Options {
             align_entries: false,
+            align_across_blank_lines: false,
             align_comments: true,
             align_single_comments: true,
             array_trailing_comma: true,
@@ -157,6 +181,7 @@ impl Default for Options {
             compact_inline_tables: false,
             compact_entries: false,
             column_width: 80,
+            reflow_comments: false,
             indent_tables: false,
             indent_entries: false,
             inline_table_expand: true,
@@ -194,6 +219,7 @@ struct Context {
     force_multiline: bool,
     errors: Rc<[TextRange]>,
     scopes: Rc<ScopedOptions>,
+    layout: Rc<dyn Layout>,
 }
 
 impl Default for Context {
@@ -203,6 +229,7 @@ impl Default for Context {
             force_multiline: Default::default(),
             errors: Rc::from([]),
             scopes: Default::default(),
+            layout: Rc::new(DefaultLayout),
         }
     }
 }
@@ -249,6 +276,19 @@ pub fn format(src: &str, options: Options) -> String {
     format_impl(p.into_syntax(), options, ctx)
 }
 
+/// Same as [`format()`], but the layout of values is decided by the given [`Layout`].
+pub fn format_with_layout(src: &str, options: Options, layout: Rc<dyn Layout>) -> String {
+    let p = crate::parser::parse(src);
+
+    let ctx = Context {
+        errors: p.errors.iter().map(|err| err.range).collect(),
+        layout,
+        ..Context::default()
+    };
+
+    format_impl(p.into_syntax(), options, ctx)
+}
+
 /// Formats a parsed TOML syntax tree.
 pub fn format_syntax(node: SyntaxNode, options: Options) -> String {
     let mut s = format_impl(node, options.clone(), Context::default());
@@ -360,6 +400,8 @@ struct FormattedEntry {
     key: String,
     value: String,
     comment: Option<String>,
+    /// Blank lines before the entry if it is aligned with the previous entries.
+    blank_lines: usize,
 }
 
 impl PartialEq for FormattedEntry {
@@ -440,6 +482,14 @@ fn format_root(node: SyntaxNode, options: &Options, context: &Context) -> String
     ) -> bool {
         let were_comments = !comments.is_empty();
 
+        if options.reflow_comments {
+            let indent_width: usize = context.indent(options).map(|s| s.chars().count()).sum();
+            *comments = reflow::reflow_comments(
+                comments,
+                options.column_width.saturating_sub(indent_width),
+            );
+        }
+
         for (idx, comment) in comments.drain(0..).enumerate() {
             if idx != 0 {
                 *formatted += options.newline();
@@ -454,7 +504,19 @@ fn format_root(node: SyntaxNode, options: &Options, context: &Context) -> String
     let mut dangling_newline_count = 0;
     let mut scoped_options = options.clone();
 
+    // Blank lines after entries that are only written if the next entry is not aligned with them.
+    let mut pending_newline_count = 0;
+
     for c in node.children_with_tokens() {
+        if pending_newline_count > 0
+            && (!matches!(c.kind(), ENTRY | WHITESPACE) || context.error_at(c.text_range()))
+        {
+            add_entries(&mut entry_group, &mut formatted, &scoped_options, &context);
+            formatted.extend(scoped_options.newlines(pending_newline_count));
+            pending_newline_count = 0;
+            skip_newlines = 0;
+        }
+
         if context.error_at(c.text_range()) {
             formatted += &c.to_string();
             continue;
@@ -539,9 +601,42 @@ fn format_root(node: SyntaxNode, options: &Options, context: &Context) -> String
                         skip_newlines = 0;
                     }
 
-                    entry_group.push(format_entry(node, &scoped_options, &context));
+                    let mut entry = format_entry(node, &scoped_options, &context);
+
+                    if pending_newline_count > 0 {
+                        entry.blank_lines =
+                            scoped_options.newlines(pending_newline_count).count() - 1;
+                        pending_newline_count = 0;
+                    }
+
+                    entry_group.push(entry);
                     skip_newlines += 1;
                 }
+                UNKNOWN => {
+                    if add_entries(&mut entry_group, &mut formatted, &scoped_options, &context) {
+                        formatted += scoped_options.newline();
+                        skip_newlines = 0;
+                    }
+
+                    if add_comments(
+                        &mut comment_group,
+                        &mut formatted,
+                        &context,
+                        &scoped_options,
+                    ) {
+                        formatted += scoped_options.newline();
+                        skip_newlines = 0;
+                    }
+
+                    // Unknown constructs are kept as they are.
+                    if formatted.is_empty() || formatted.ends_with('\n') {
+                        formatted.extend(context.indent(&scoped_options));
+                    } else {
+                        formatted += " ";
+                    }
+
+                    formatted += &node.to_string();
+                }
                 _ => unreachable!(),
             },
             NodeOrToken::Token(token) => match token.kind() {
@@ -559,6 +654,15 @@ fn format_root(node: SyntaxNode, options: &Options, context: &Context) -> String
                         }
                     }
 
+                    if newline_count > 1
+                        && scoped_options.align_across_blank_lines
+                        && comment_group.is_empty()
+                        && !entry_group.is_empty()
+                    {
+                        pending_newline_count = newline_count;
+                        continue;
+                    }
+
                     if newline_count > 1 {
                         add_comments(
                             &mut comment_group,
@@ -588,6 +692,11 @@ fn format_root(node: SyntaxNode, options: &Options, context: &Context) -> String
         }
     }
 
+    if pending_newline_count > 0 {
+        add_entries(&mut entry_group, &mut formatted, &scoped_options, &context);
+        formatted.extend(scoped_options.newlines(pending_newline_count));
+    }
+
     add_comments(
         &mut comment_group,
         &mut formatted,
@@ -641,28 +750,44 @@ fn add_entries(
     let were_entries = !entry_group.is_empty();
 
     if options.reorder_keys {
-        entry_group.sort();
+        // Entries separated by blank lines are reordered separately.
+        let mut start = 0;
+        while start < entry_group.len() {
+            let end = entry_group[start + 1..]
+                .iter()
+                .position(|e| e.blank_lines > 0)
+                .map_or(entry_group.len(), |idx| start + 1 + idx);
+
+            let blank_lines = mem::take(&mut entry_group[start].blank_lines);
+            entry_group[start..end].sort();
+            entry_group[start].blank_lines = blank_lines;
+
+            start = end;
+        }
     }
 
     let indent_chars_count = context.indent_level * options.indent_string.chars().count();
 
     // We check for too long lines, and try to expand them if possible.
     // We don't take vertical alignment into account for simplicity.
-    if options.array_auto_expand {
-        for entry in entry_group.iter_mut() {
-            let comment_chars_count = entry
-                .comment
-                .as_ref()
-                .map(
-                    |c| c.chars().count() + 1, // account for the separator ' ' as well
-                )
-                .unwrap_or(0);
-
-            let line_count = entry.value.split('\n').count();
-
-            // check each line of the value
-            // for the first line we include the actual indent, key, and the eq parts as well
-            for (idx, line) in entry.value.split('\n').enumerate() {
+    for entry in entry_group.iter_mut() {
+        let comment_chars_count = entry
+            .comment
+            .as_ref()
+            .map(
+                |c| c.chars().count() + 1, // account for the separator ' ' as well
+            )
+            .unwrap_or(0);
+
+        let line_count = entry.value.split('\n').count();
+
+        // check each line of the value
+        // for the first line we include the actual indent, key, and the eq parts as well
+        let width = entry
+            .value
+            .split('\n')
+            .enumerate()
+            .map(|(idx, line)| {
                 let mut chars_count = line.chars().count();
                 if idx == 0 {
                     chars_count += indent_chars_count;
@@ -675,38 +800,51 @@ fn add_entries(
                     chars_count += comment_chars_count;
                 }
 
-                if chars_count > options.column_width {
-                    let mut context = context.clone();
-                    context.force_multiline = true;
-
-                    // too long, reformat the value of the entry
-                    let value = format_value(
-                        entry
-                            .syntax
-                            .as_node()
-                            .unwrap()
-                            .children()
-                            .find(|n| n.kind() == VALUE)
-                            .unwrap(),
-                        options,
-                        &context,
-                    );
-
-                    entry.value.clear();
+                chars_count
+            })
+            .max()
+            .unwrap_or_default();
+
+        let stats = EntryStats {
+            indent_level: context.indent_level,
+            width,
+            line_count,
+        };
+
+        if context.layout.expand_entry(&stats, options) {
+            let mut context = context.clone();
+            context.force_multiline = true;
+
+            // too long, reformat the value of the entry
+            let value = format_value(
+                entry
+                    .syntax
+                    .as_node()
+                    .unwrap()
+                    .children()
+                    .find(|n| n.kind() == VALUE)
+                    .unwrap(),
+                options,
+                &context,
+            );
 
-                    if let Some(c) = value.trailing_comment() {
-                        debug_assert!(entry.comment.is_none());
-                        entry.comment = Some(c);
-                    }
+            entry.value.clear();
 
-                    value.write_to(&mut entry.value, options);
-                    break;
-                }
+            if let Some(c) = value.trailing_comment() {
+                debug_assert!(entry.comment.is_none());
+                entry.comment = Some(c);
             }
+
+            value.write_to(&mut entry.value, options);
         }
     }
 
     let mut comment_count = 0;
+    let blank_lines = entry_group
+        .iter()
+        .map(|e| e.blank_lines)
+        .collect::<Vec<_>>();
+
     // Transform the entries into generic rows that can be aligned.
     let rows = entry_group
         .drain(0..)
@@ -743,6 +881,7 @@ fn add_entries(
             1..usize::MAX
         },
         &rows,
+        &blank_lines,
         options.newline(),
         " ",
     );
@@ -788,6 +927,7 @@ fn format_entry(node: SyntaxNode, options: &Options, context: &Context) -> Forma
         key,
         value,
         comment,
+        blank_lines: 0,
     }
 }
 
@@ -839,6 +979,10 @@ fn format_value(node: SyntaxNode, options: &Options, context: &Context) -> impl
 
                     formatted.write_to(&mut value, options);
                 }
+                UNKNOWN => {
+                    debug_assert!(value.is_empty());
+                    value = n.to_string();
+                }
                 _ => unreachable!(),
             },
             NodeOrToken::Token(t) => match t.kind() {
@@ -865,14 +1009,18 @@ fn format_inline_table(
     let mut formatted = String::new();
     let mut comment = None;
 
+    let child_count = node.children().count();
+
     let mut context = context.clone();
     if context.force_multiline {
-        context.force_multiline = options.inline_table_expand;
+        let stats = InlineTableStats {
+            indent_level: context.indent_level,
+            entry_count: child_count,
+        };
+        context.force_multiline = context.layout.expand_inline_table(&stats, options);
     }
     let context = &context;
 
-    let child_count = node.children().count();
-
     if node.children().count() == 0 {
         formatted = "{}".into();
     }
@@ -937,15 +1085,17 @@ fn can_collapse_array(node: &SyntaxNode) -> bool {
 }
 
 fn format_array(node: SyntaxNode, options: &Options, context: &Context) -> impl FormattedItem {
-    let mut multiline = is_array_multiline(&node) || context.force_multiline;
+    let stats = ArrayStats {
+        indent_level: context.indent_level,
+        item_count: node.children().count(),
+        multiline: is_array_multiline(&node),
+        comments: !can_collapse_array(&node),
+        expanded: context.force_multiline,
+    };
+    let multiline = context.layout.multiline_array(&stats, options);
 
     let mut formatted = String::new();
 
-    // We always try to collapse it if possible.
-    if can_collapse_array(&node) && options.array_auto_collapse && !context.force_multiline {
-        multiline = false;
-    }
-
     // We use the same strategy as for entries, refer to [`format_root`].
     let mut skip_newlines = 0;
 
@@ -1006,6 +1156,7 @@ fn format_array(node: SyntaxNode, options: &Options, context: &Context) -> impl
             if align_comments { 0..usize::MAX } else { 0..0 },
             1..usize::MAX,
             &rows,
+            &[],
             options.newline(),
             " ",
         );
@@ -1220,6 +1371,7 @@ fn format_rows<R, S>(
     align_range: Range<usize>,
     separator_range: Range<usize>,
     rows: &[R],
+    blank_lines: &[usize],
     newline: &str,
     separator: &str,
 ) -> String
@@ -1264,6 +1416,9 @@ where
     for (row_idx, row) in rows.iter().enumerate() {
         if row_idx != 0 {
             out += newline;
+            for _ in 0..blank_lines.get(row_idx).copied().unwrap_or(0) {
+                out += newline;
+            }
         }
 
         let mut last_align_idx = 0_usize;
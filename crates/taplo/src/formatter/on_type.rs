@@ -0,0 +1,320 @@
+//! Small edits for formatting while typing.
+//!
+//! The document is usually incomplete while it is being typed,
+//! so instead of the syntax tree only the tokens around the cursor are used.
+
+use super::Options;
+use crate::syntax::SyntaxKind::{self, *};
+use logos::Logos;
+use rowan::{TextRange, TextSize};
+use std::ops::Range;
+
+/// The characters [`format_on_type`] reacts to.
+pub const TRIGGER_CHARACTERS: &[char] = &[']', '\n', '='];
+
+/// A replacement of a range in the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub range: TextRange,
+    pub text: String,
+}
+
+impl Edit {
+    fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Self {
+            range: TextRange::new(
+                TextSize::from(range.start as u32),
+                TextSize::from(range.end as u32),
+            ),
+            text: text.into(),
+        }
+    }
+}
+
+/// Returns the edits to apply after `ch` was typed,
+/// `offset` is the position right after the typed character.
+///
+/// - `]` after `[[` closes the array of tables header.
+/// - A new line inside an array is indented, and a comma is added
+///   after the previous item if `array_trailing_comma` is set.
+/// - `=` after a key is surrounded by spaces unless `compact_entries` is set.
+pub fn format_on_type(src: &str, offset: TextSize, ch: char, options: Options) -> Vec<Edit> {
+    let tokens = Tokens::new(src);
+
+    let offset = u32::from(offset) as usize;
+
+    let idx = match tokens.ending_at(offset) {
+        Some(idx) => idx,
+        None => return Vec::new(),
+    };
+
+    match ch {
+        ']' => close_array_header(&tokens, idx),
+        '\n' => array_newline(&tokens, idx, offset, &options),
+        '=' => entry_separator(&tokens, idx, &options),
+        _ => Vec::new(),
+    }
+}
+
+struct Token<'s> {
+    kind: SyntaxKind,
+    range: Range<usize>,
+    text: &'s str,
+}
+
+struct Tokens<'s> {
+    src: &'s str,
+    tokens: Vec<Token<'s>>,
+}
+
+impl<'s> Tokens<'s> {
+    fn new(src: &'s str) -> Self {
+        let mut lexer = SyntaxKind::lexer(src);
+        let mut tokens = Vec::new();
+
+        while let Some(kind) = lexer.next() {
+            tokens.push(Token {
+                kind,
+                range: lexer.span(),
+                text: lexer.slice(),
+            });
+        }
+
+        Self { src, tokens }
+    }
+
+    /// The index of the token that contains the character before the offset.
+    fn ending_at(&self, offset: usize) -> Option<usize> {
+        self.tokens
+            .iter()
+            .position(|t| t.range.start < offset && offset <= t.range.end)
+    }
+
+    fn kind(&self, idx: Option<usize>) -> Option<SyntaxKind> {
+        idx.and_then(|idx| self.tokens.get(idx)).map(|t| t.kind)
+    }
+
+    /// The index of the closest token before `idx` that is not one of `skip`.
+    fn prev_except(&self, idx: usize, skip: &[SyntaxKind]) -> Option<usize> {
+        (0..idx)
+            .rev()
+            .find(|&i| !skip.contains(&self.tokens[i].kind))
+    }
+
+    /// The index of the closest token after `idx` that is not one of `skip`.
+    fn next_except(&self, idx: usize, skip: &[SyntaxKind]) -> Option<usize> {
+        (idx + 1..self.tokens.len()).find(|&i| !skip.contains(&self.tokens[i].kind))
+    }
+
+    /// The whitespace at the start of the line of the token.
+    fn line_indent(&self, idx: usize) -> &'s str {
+        let line_start = (0..idx)
+            .rev()
+            .find(|&i| self.tokens[i].kind == NEWLINE)
+            .map_or(0, |i| i + 1);
+
+        match self.tokens.get(line_start) {
+            Some(t) if t.kind == WHITESPACE => t.text,
+            _ => "",
+        }
+    }
+
+    /// The index of the unclosed `[` of the array that contains the token.
+    fn array_start(&self, idx: usize) -> Option<usize> {
+        let mut brackets = 0_usize;
+        let mut braces = 0_usize;
+
+        for i in (0..idx).rev() {
+            match self.tokens[i].kind {
+                BRACKET_END => brackets += 1,
+                BRACE_END => braces += 1,
+                BRACE_START => {
+                    if braces == 0 {
+                        // The token is in an inline table.
+                        return None;
+                    }
+                    braces -= 1;
+                }
+                BRACKET_START => {
+                    if brackets == 0 {
+                        return if self.is_array_start(i) {
+                            Some(i)
+                        } else {
+                            None
+                        };
+                    }
+                    brackets -= 1;
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Whether the `[` starts an array value and not a table header.
+    fn is_array_start(&self, idx: usize) -> bool {
+        let mut idx = idx;
+        loop {
+            let prev = self.prev_except(idx, &[WHITESPACE, NEWLINE, COMMENT]);
+            match self.kind(prev) {
+                Some(EQ | COMMA) => return true,
+                // Nested arrays.
+                Some(BRACKET_START) => idx = prev.unwrap(),
+                _ => return false,
+            }
+        }
+    }
+}
+
+fn close_array_header(tokens: &Tokens, idx: usize) -> Vec<Edit> {
+    if tokens.kind(Some(idx)) != Some(BRACKET_END) {
+        return Vec::new();
+    }
+
+    // The header is already closed.
+    if tokens.kind(tokens.next_except(idx, &[WHITESPACE])) == Some(BRACKET_END) {
+        return Vec::new();
+    }
+
+    let bracket = tokens.prev_except(
+        idx,
+        &[
+            WHITESPACE,
+            PERIOD,
+            IDENT,
+            STRING,
+            STRING_LITERAL,
+            INTEGER,
+            FLOAT,
+            BOOL,
+        ],
+    );
+
+    let header_start = match bracket {
+        Some(i)
+            if i > 0
+                && tokens.kind(Some(i)) == Some(BRACKET_START)
+                && tokens.kind(Some(i - 1)) == Some(BRACKET_START) =>
+        {
+            i - 1
+        }
+        _ => return Vec::new(),
+    };
+
+    if !matches!(
+        tokens.kind(tokens.prev_except(header_start, &[WHITESPACE])),
+        None | Some(NEWLINE)
+    ) {
+        return Vec::new();
+    }
+
+    vec![Edit::new(
+        tokens.tokens[idx].range.end..tokens.tokens[idx].range.end,
+        "]",
+    )]
+}
+
+fn array_newline(tokens: &Tokens, idx: usize, offset: usize, options: &Options) -> Vec<Edit> {
+    // The editor might have indented the new line already.
+    let (newline, line_start, indent_end) = match tokens.kind(Some(idx)) {
+        Some(NEWLINE) => {
+            let indent_end = match tokens.tokens.get(idx + 1) {
+                Some(t) if t.kind == WHITESPACE && tokens.tokens[idx].range.end == offset => {
+                    t.range.end
+                }
+                _ => offset,
+            };
+            (idx, offset, indent_end)
+        }
+        Some(WHITESPACE) if idx > 0 && tokens.kind(Some(idx - 1)) == Some(NEWLINE) => {
+            let whitespace = &tokens.tokens[idx].range;
+            (idx - 1, whitespace.start, whitespace.end)
+        }
+        _ => return Vec::new(),
+    };
+
+    let array_start = match tokens.array_start(newline) {
+        Some(i) => i,
+        None => return Vec::new(),
+    };
+
+    let mut edits = Vec::new();
+
+    if options.array_trailing_comma {
+        if let Some(prev) = tokens.prev_except(newline, &[WHITESPACE, COMMENT]) {
+            if prev > array_start
+                && !matches!(
+                    tokens.kind(Some(prev)),
+                    Some(COMMA | NEWLINE | BRACKET_START | BRACE_START | EQ)
+                )
+            {
+                let end = tokens.tokens[prev].range.end;
+                edits.push(Edit::new(end..end, ","));
+            }
+        }
+    }
+
+    let base_indent = tokens.line_indent(array_start);
+
+    // The closing bracket is on the new line.
+    let closing = indent_end == tokens.tokens[newline].range.end
+        && tokens.kind(tokens.next_except(newline, &[WHITESPACE])) == Some(BRACKET_END);
+
+    let indent = if closing {
+        base_indent.to_string()
+    } else {
+        format!("{}{}", base_indent, options.indent_string)
+    };
+
+    if tokens.src[line_start..indent_end] != indent {
+        edits.push(Edit::new(line_start..indent_end, indent));
+    }
+
+    edits
+}
+
+fn entry_separator(tokens: &Tokens, idx: usize, options: &Options) -> Vec<Edit> {
+    if tokens.kind(Some(idx)) != Some(EQ) {
+        return Vec::new();
+    }
+
+    const KEY_TOKENS: &[SyntaxKind] = &[
+        IDENT,
+        IDENT_WITH_GLOB,
+        STRING,
+        STRING_LITERAL,
+        INTEGER,
+        FLOAT,
+        BOOL,
+    ];
+
+    let key = match tokens.prev_except(idx, &[WHITESPACE]) {
+        Some(i) if KEY_TOKENS.contains(&tokens.tokens[i].kind) => &tokens.tokens[i],
+        _ => return Vec::new(),
+    };
+
+    // Only the first `=` of an entry, the key must be at the start of the line or in an inline table.
+    let before_key = tokens.prev_except(idx, &[&[WHITESPACE, PERIOD], KEY_TOKENS].concat());
+    if !matches!(
+        tokens.kind(before_key),
+        None | Some(NEWLINE | BRACE_START | COMMA)
+    ) {
+        return Vec::new();
+    }
+
+    let eq = &tokens.tokens[idx];
+    let space = if options.compact_entries { "" } else { " " };
+
+    let mut edits = Vec::new();
+
+    if tokens.src[key.range.end..eq.range.start] != *space {
+        edits.push(Edit::new(key.range.end..eq.range.start, space));
+    }
+
+    if !options.compact_entries && tokens.kind(Some(idx + 1)) != Some(WHITESPACE) {
+        edits.push(Edit::new(eq.range.end..eq.range.end, space));
+    }
+
+    edits
+}
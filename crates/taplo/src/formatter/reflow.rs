@@ -0,0 +1,161 @@
+//! Reflowing blocks of comments to the column width.
+
+/// Reflow the paragraphs of a block of consecutive comment lines
+/// that have lines longer than `width` characters.
+///
+/// Paragraphs are separated by empty comments, list items start
+/// new paragraphs and their indented continuation lines are part of them.
+///
+/// The following lines are never changed:
+///
+/// - directives such as `#:schema`,
+/// - fenced code blocks and everything in them,
+/// - headings, tables and quotes,
+/// - lines that are indented deeper than the paragraph, e.g. code.
+pub(super) fn reflow_comments(comments: &[String], width: usize) -> Vec<String> {
+    let mut reflowed = Vec::with_capacity(comments.len());
+    let mut paragraph: Option<Paragraph> = None;
+    let mut fence: Option<&str> = None;
+
+    for comment in comments {
+        let comment = comment.as_str();
+        let prefix_len = comment.len() - comment.trim_start_matches('#').len();
+        let (prefix, body) = comment.split_at(prefix_len);
+        let text = body.trim_start();
+        let lead = &body[..body.len() - text.len()];
+
+        if let Some(marker) = fence {
+            if text.starts_with(marker) {
+                fence = None;
+            }
+            reflowed.push(comment.to_string());
+            continue;
+        }
+
+        let verbatim = prefix.is_empty()
+            || text.is_empty()
+            || (lead.chars().count() > 1
+                && !matches!(&paragraph, Some(p) if p.continues(prefix, lead)))
+            || body.starts_with(':')
+            || body.starts_with('!')
+            || text.starts_with(['#', '|', '>']);
+
+        if verbatim || text.starts_with("```") || text.starts_with("~~~") {
+            if let Some(p) = paragraph.take() {
+                p.write_to(&mut reflowed, width);
+            }
+
+            if !verbatim {
+                fence = Some(&text[..3]);
+            }
+
+            reflowed.push(comment.to_string());
+            continue;
+        }
+
+        match &mut paragraph {
+            Some(p) if p.continues(prefix, lead) && list_marker(text).is_none() => {
+                p.push_line(comment, text);
+            }
+            _ => {
+                if let Some(p) = paragraph.take() {
+                    p.write_to(&mut reflowed, width);
+                }
+
+                let mut p = Paragraph::new(prefix, lead, text);
+                p.push_line(comment, text);
+                paragraph = Some(p);
+            }
+        }
+    }
+
+    if let Some(p) = paragraph {
+        p.write_to(&mut reflowed, width);
+    }
+
+    reflowed
+}
+
+/// The list marker and the space after it at the start of the text, if any.
+fn list_marker(text: &str) -> Option<&str> {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    let marker_len = if digits == 0 {
+        text.starts_with(['-', '*', '+']).then_some(1)?
+    } else {
+        text[digits..]
+            .starts_with(['.', ')'])
+            .then_some(digits + 1)?
+    };
+
+    text[marker_len..]
+        .starts_with(' ')
+        .then(|| &text[..marker_len + 1])
+}
+
+struct Paragraph<'c> {
+    /// The `#` characters of the comments.
+    prefix: &'c str,
+    /// Whitespace and the list marker before the text of the first line.
+    first_lead: String,
+    /// Whitespace before the text of the rest of the lines.
+    lead: String,
+    lines: Vec<&'c str>,
+    words: Vec<&'c str>,
+}
+
+impl<'c> Paragraph<'c> {
+    fn new(prefix: &'c str, lead: &'c str, text: &'c str) -> Self {
+        let marker = list_marker(text).unwrap_or_default();
+
+        Self {
+            prefix,
+            first_lead: format!("{lead}{marker}"),
+            lead: format!("{lead}{}", " ".repeat(marker.len())),
+            lines: Vec::new(),
+            words: Vec::new(),
+        }
+    }
+
+    /// Whether a line with the given prefix and leading whitespace can continue the paragraph.
+    fn continues(&self, prefix: &str, lead: &str) -> bool {
+        self.prefix == prefix && (self.lead == lead || self.lead.len() <= 1 && lead.len() <= 1)
+    }
+
+    fn push_line(&mut self, line: &'c str, text: &'c str) {
+        self.lines.push(line);
+        self.words.extend(
+            text.strip_prefix(self.first_lead.trim_start())
+                .filter(|_| self.lines.len() == 1)
+                .unwrap_or(text)
+                .split_whitespace(),
+        );
+    }
+
+    fn write_to(self, reflowed: &mut Vec<String>, width: usize) {
+        if self.lines.iter().all(|line| line.chars().count() <= width) {
+            reflowed.extend(self.lines.into_iter().map(Into::into));
+            return;
+        }
+
+        let mut line = format!("{}{}", self.prefix, self.first_lead);
+        let mut empty = true;
+
+        for word in self.words {
+            if !empty && line.chars().count() + 1 + word.chars().count() > width {
+                reflowed.push(line);
+                line = format!("{}{}", self.prefix, self.lead);
+                empty = true;
+            }
+
+            if !empty {
+                line.push(' ');
+            }
+
+            line += word;
+            empty = false;
+        }
+
+        reflowed.push(line);
+    }
+}
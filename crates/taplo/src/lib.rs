@@ -17,6 +17,7 @@
 //!
 //! - **serde**: Support for [serde](https://serde.rs) serialization of the DOM nodes.
 //! - **schema**: Enable JSON-schema generation for formatter configuration.
+//! - **cargo**: Typed views of `Cargo.toml` manifests in [`cargo`].
 //!
 //! # Usage
 //!
@@ -50,8 +51,12 @@
 //! assert!(root_node.validate().is_err());
 //! ```
 
+#[cfg(feature = "cargo")]
+pub mod cargo;
+pub mod diagnostics;
 pub mod dom;
 pub mod formatter;
+pub mod messages;
 pub mod parser;
 pub mod syntax;
 pub mod util;
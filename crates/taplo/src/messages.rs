@@ -0,0 +1,349 @@
+//! User-facing diagnostic messages.
+//!
+//! Every message has a stable code and optional named parameters,
+//! the text is looked up by the code in a [`Catalog`] so that
+//! it can be replaced with a translation.
+//!
+//! Templates refer to the parameters by name in braces, e.g. `expected "{symbol}"`.
+//!
+//! ```
+//! use taplo::messages::{Catalog, Message};
+//!
+//! let message = Message::new("expected-symbol").with_arg("symbol", "=");
+//! assert_eq!(message.to_string(), r#"expected "=""#);
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.insert("expected-symbol", r#""{symbol}" erwartet"#);
+//! assert_eq!(catalog.format(&message), r#""=" erwartet"#);
+//! ```
+
+use crate::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The default English templates of all messages by their codes.
+pub const DEFAULT_MESSAGES: &[(&str, &str)] = &[
+    // Syntax errors.
+    ("expected-symbol", r#"expected "{symbol}""#),
+    ("unexpected-symbol", r#"unexpected "{symbol}""#),
+    ("expected-identifier", "expected identifier"),
+    ("unexpected-identifier", "unexpected identifier"),
+    ("expected-value", "expected value"),
+    ("expected-new-line", "expected new line"),
+    ("unexpected-token", "unexpected token"),
+    ("unexpected-eof", "unexpected end of input"),
+    (
+        "trailing-comma",
+        "expected value, trailing comma is not allowed",
+    ),
+    (
+        "inline-table-new-line",
+        "newline is not allowed in an inline table",
+    ),
+    ("invalid-underscores", "invalid underscores"),
+    (
+        "zero-padded-integer",
+        "zero-padded integers are not allowed",
+    ),
+    ("zero-padded-number", "zero-padded numbers are not allowed"),
+    ("invalid-comment-character", "invalid character in comment"),
+    ("invalid-string-character", "invalid character in string"),
+    (
+        "invalid-control-character",
+        "invalid control character in string literal",
+    ),
+    ("invalid-escape-sequence", "invalid escape sequence"),
+    ("invalid-toml", "invalid TOML"),
+    // Semantic errors.
+    (
+        "unexpected-syntax",
+        "the syntax was not expected here: {syntax}",
+    ),
+    (
+        "invalid-escape-sequences",
+        "the string contains invalid escape sequence(s)",
+    ),
+    ("conflicting-keys", "conflicting keys"),
+    ("expected-table", "expected table"),
+    ("expected-array-of-tables", "expected array of tables"),
+    ("key-not-found", "the key or index was not found"),
+    ("invalid-glob", "invalid glob pattern: {error}"),
+    ("invalid-key", "the given key is invalid: {error}"),
+    ("other-key-defined-here", "other key defined here"),
+    ("required-by-this-key", "required by this key"),
+    ("table-defined-here", "table defined here"),
+    (
+        "array-of-tables-defined-here",
+        "array of tables defined here",
+    ),
+    // Schema diagnostics.
+    ("deprecated-key", r#""{key}" is deprecated"#),
+    (
+        "deprecated-key-replacement",
+        r#""{key}" is deprecated, use "{replacement}" instead"#,
+    ),
+    ("deprecated-key-note", r#""{key}" is deprecated: {note}"#),
+    (
+        "deprecated-key-replacement-note",
+        r#""{key}" is deprecated, use "{replacement}" instead: {note}"#,
+    ),
+    ("deprecated-key-label", "deprecated key"),
+    ("schema-type", "{value} is not of type {types}"),
+    ("schema-required", "{property} is a required property"),
+    (
+        "schema-additional-properties",
+        "additional properties are not allowed: {properties}",
+    ),
+    (
+        "schema-additional-items",
+        "additional items are not allowed, expected at most {limit}",
+    ),
+    ("schema-enum", "{value} is not one of {options}"),
+    ("schema-const", "{expected} was expected"),
+    ("schema-format", r#"{value} is not a "{format}""#),
+    ("schema-pattern", r#"{value} does not match "{pattern}""#),
+    (
+        "schema-minimum",
+        "{value} is less than the minimum of {limit}",
+    ),
+    (
+        "schema-maximum",
+        "{value} is greater than the maximum of {limit}",
+    ),
+    (
+        "schema-exclusive-minimum",
+        "{value} is less than or equal to the minimum of {limit}",
+    ),
+    (
+        "schema-exclusive-maximum",
+        "{value} is greater than or equal to the maximum of {limit}",
+    ),
+    (
+        "schema-min-length",
+        "{value} is shorter than the minimum length of {limit}",
+    ),
+    (
+        "schema-max-length",
+        "{value} is longer than the maximum length of {limit}",
+    ),
+    ("schema-min-items", "{value} has less than {limit} item(s)"),
+    ("schema-max-items", "{value} has more than {limit} item(s)"),
+    (
+        "schema-min-properties",
+        "{value} has less than {limit} propert(y/ies)",
+    ),
+    (
+        "schema-max-properties",
+        "{value} has more than {limit} propert(y/ies)",
+    ),
+    (
+        "schema-multiple-of",
+        "{value} is not a multiple of {multiple}",
+    ),
+    ("schema-unique-items", "{value} has non-unique elements"),
+    (
+        "schema-any-of",
+        "{value} is not valid under any of the given schemas",
+    ),
+    (
+        "schema-one-of-multiple",
+        "{value} is valid under more than one of the given schemas",
+    ),
+    (
+        "schema-contains",
+        "none of {value} are valid under the given schema",
+    ),
+    ("schema-not", "{value} is not allowed"),
+    ("schema-property-names", "invalid property name: {error}"),
+    ("schema-error", "{error}"),
+    ("document-excluded", "this document has been excluded"),
+    // Merges and diffs.
+    ("merge-conflict", "conflicting changes to `{keys}`"),
+    ("merge-ours", "our version"),
+    ("merge-theirs", "their version"),
+    ("merge-ours-file", "{path} (ours)"),
+    ("merge-theirs-file", "{path} (theirs)"),
+    ("merge-removed-ours", "the value was removed in our version"),
+    (
+        "merge-removed-theirs",
+        "the value was removed in their version",
+    ),
+    ("diff-modified", "{keys} = {old} -> {new}"),
+    // Quick fixes.
+    ("quick-fix-convert", "Convert to {kind}"),
+    ("quick-fix-rename", r#"Rename to "{key}""#),
+    ("quick-fix-move", r#"Move to "{key}""#),
+];
+
+/// A message identified by its code with the values of its parameters.
+///
+/// It is displayed with the default English template.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Message {
+    pub code: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    #[must_use]
+    pub fn new(code: &'static str) -> Self {
+        Self {
+            code,
+            args: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+impl core::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Catalog::default().format(self).fmt(f)
+    }
+}
+
+/// Message templates by code, the default English templates
+/// are used for codes that are missing from the catalog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Catalog {
+    templates: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// An empty catalog that only contains the default templates.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the template for a code, returns the previous template if any.
+    pub fn insert(
+        &mut self,
+        code: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Option<String> {
+        self.templates.insert(code.into(), template.into())
+    }
+
+    /// The template for the code, or the default template if the catalog does not have one.
+    #[must_use]
+    pub fn template(&self, code: &str) -> Option<&str> {
+        self.templates.get(code).map(String::as_str).or_else(|| {
+            DEFAULT_MESSAGES
+                .iter()
+                .find(|(c, _)| *c == code)
+                .map(|(_, template)| *template)
+        })
+    }
+
+    /// Format the message with the template from this catalog.
+    ///
+    /// Unknown codes are formatted as the code itself,
+    /// parameters that are missing from the message are left as-is.
+    #[must_use]
+    pub fn format(&self, message: &Message) -> String {
+        let template = match self.template(message.code) {
+            Some(t) => t,
+            None => return message.code.to_string(),
+        };
+
+        let mut s = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            s.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let arg = rest.find('}').and_then(|end| {
+                let name = &rest[1..end];
+                message
+                    .args
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| (end, value))
+            });
+
+            match arg {
+                Some((end, value)) => {
+                    s.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    s.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        s.push_str(rest);
+        s
+    }
+}
+
+impl<C: Into<String>, T: Into<String>> FromIterator<(C, T)> for Catalog {
+    fn from_iter<I: IntoIterator<Item = (C, T)>>(iter: I) -> Self {
+        let mut catalog = Catalog::new();
+        catalog.extend(iter);
+        catalog
+    }
+}
+
+impl<C: Into<String>, T: Into<String>> Extend<(C, T)> for Catalog {
+    fn extend<I: IntoIterator<Item = (C, T)>>(&mut self, iter: I) {
+        for (code, template) in iter {
+            self.insert(code, template);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Catalog, Message, DEFAULT_MESSAGES};
+
+    #[test]
+    fn default_messages_are_unique() {
+        for (i, (code, _)) in DEFAULT_MESSAGES.iter().enumerate() {
+            assert!(
+                DEFAULT_MESSAGES[i + 1..].iter().all(|(c, _)| c != code),
+                "duplicate message code {code}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_messages() {
+        let catalog: Catalog = [
+            ("expected-symbol", "{symbol} {missing} {{symbol}"),
+            ("custom", "{a}{b}"),
+        ]
+        .into_iter()
+        .collect();
+
+        let message = Message::new("expected-symbol").with_arg("symbol", "]");
+        assert_eq!(message.to_string(), r#"expected "]""#);
+        assert_eq!(catalog.format(&message), "] {missing} {]");
+
+        assert_eq!(
+            catalog.format(&Message::new("custom").with_arg("a", "{b}").with_arg("b", 1)),
+            "{b}1"
+        );
+
+        assert_eq!(
+            catalog.format(&Message::new("conflicting-keys")),
+            "conflicting keys"
+        );
+        assert_eq!(
+            catalog.format(&Message::new("unknown-code")),
+            "unknown-code"
+        );
+
+        let error = crate::dom::Error::from(crate::dom::error::QueryError::NotFound);
+        assert_eq!(error.message(), Message::new("key-not-found"));
+        assert_eq!(error.to_string(), "the key or index was not found");
+    }
+}
@@ -0,0 +1,136 @@
+use rowan::{TextRange, TextSize};
+
+/// A `#:taplo` comment that controls which diagnostics are reported.
+///
+/// - `#:taplo ignore <rule>...` ignores the diagnostics in the entire document,
+///   at least one rule is required so that a mistake cannot hide every diagnostic.
+/// - `#:taplo disable-next-line [rule...]` ignores the diagnostics in the line after the comment.
+///
+/// Rules are the codes of the diagnostics, they can be separated
+/// by spaces or commas.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Directive {
+    /// The range of the comment.
+    pub range: TextRange,
+    pub kind: DirectiveKind,
+    /// The codes of the diagnostics the directive applies to,
+    /// all diagnostics if it is empty, which is only allowed for `disable-next-line`.
+    pub rules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirectiveKind {
+    Ignore,
+    DisableNextLine {
+        /// The range of the line after the comment without the line ending.
+        line: TextRange,
+    },
+}
+
+impl Directive {
+    /// Returns the directive if the comment is one,
+    /// `rest` is the source text after the comment.
+    pub(crate) fn parse(comment: &str, range: TextRange, rest: &str) -> Option<Self> {
+        let content = comment.strip_prefix("#:taplo")?;
+
+        if !content.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        let mut words = content
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| !w.is_empty());
+
+        let kind = match words.next()? {
+            "ignore" => DirectiveKind::Ignore,
+            "disable-next-line" => {
+                let line = match rest.find('\n') {
+                    Some(start) => {
+                        let line = &rest[start + 1..];
+                        let len = line.find('\n').unwrap_or(line.len());
+                        let len = line[..len].trim_end_matches('\r').len();
+
+                        let start = range.end() + TextSize::from(start as u32 + 1);
+                        TextRange::at(start, TextSize::from(len as u32))
+                    }
+                    None => TextRange::empty(range.end() + TextSize::of(rest)),
+                };
+
+                DirectiveKind::DisableNextLine { line }
+            }
+            _ => return None,
+        };
+
+        let rules: Vec<String> = words.map(Into::into).collect();
+
+        if kind == DirectiveKind::Ignore && rules.is_empty() {
+            return None;
+        }
+
+        Some(Self { range, kind, rules })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Directive, DirectiveKind};
+    use rowan::{TextRange, TextSize};
+
+    fn parse(comment: &str, rest: &str) -> Option<Directive> {
+        Directive::parse(
+            comment,
+            TextRange::at(0.into(), TextSize::of(comment)),
+            rest,
+        )
+    }
+
+    #[test]
+    fn malformed_directives() {
+        for comment in [
+            "#:taplo",
+            "#:taplo ",
+            "#:taploignore",
+            "#: taplo ignore",
+            "#:taplo Ignore",
+            "#:taplo unknown rule",
+            "# taplo ignore",
+            "#:taplo ignore",
+            "#:taplo\tignore , ",
+        ] {
+            assert_eq!(parse(comment, ""), None, "{comment}");
+        }
+    }
+
+    #[test]
+    fn rules() {
+        let directive = parse("#:taplo ignore E0001,,  conflicting-keys,", "").unwrap();
+        assert_eq!(directive.kind, DirectiveKind::Ignore);
+        assert_eq!(directive.rules, ["E0001", "conflicting-keys"]);
+
+        let directive = parse("#:taplo\tdisable-next-line", "").unwrap();
+        assert!(directive.rules.is_empty());
+    }
+
+    #[test]
+    fn next_line() {
+        let comment = "#:taplo disable-next-line";
+        let end = TextSize::of(comment);
+
+        let directive = parse(comment, "\r\na = 1\r\nb = 2").unwrap();
+        assert_eq!(
+            directive.kind,
+            DirectiveKind::DisableNextLine {
+                line: TextRange::at(end + TextSize::from(2), TextSize::from(5))
+            }
+        );
+
+        // The comment is on the last line.
+        let directive = parse(comment, "  ").unwrap();
+        assert_eq!(
+            directive.kind,
+            DirectiveKind::DisableNextLine {
+                line: TextRange::empty(end + TextSize::from(2))
+            }
+        );
+    }
+}
@@ -2,24 +2,31 @@
 
 use crate::{
     dom::{self, FromSyntax},
+    messages::Message,
     syntax::{SyntaxKind, SyntaxKind::*, SyntaxNode},
-    util::{allowed_chars, check_escape},
+    util::{allowed_chars, check_escape_ranges},
 };
 use logos::{Lexer, Logos};
 use rowan::{GreenNode, GreenNodeBuilder, TextRange, TextSize};
-use std::convert::TryInto;
+use std::{convert::TryInto, ops::Range};
 
 #[macro_use]
 mod macros;
 
+mod directive;
+pub use directive::{Directive, DirectiveKind};
+
+mod unknown;
+
 /// A syntax error that can occur during parsing.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Error {
     /// The span of the error.
     pub range: TextRange,
 
-    /// Human-friendly error message.
-    pub message: String,
+    /// Human-friendly error message,
+    /// it can be formatted with a [`Catalog`](crate::messages::Catalog) for translations.
+    pub message: Message,
 }
 
 impl core::fmt::Display for Error {
@@ -44,6 +51,45 @@ pub fn parse(source: &str) -> Parse {
     Parser::new(source).parse()
 }
 
+/// Parse a TOML document like [`parse`], but preserve the constructs that
+/// cannot be parsed instead of reporting them as syntax errors.
+///
+/// This allows processing documents that use syntax from newer TOML versions
+/// or extensions.
+///
+/// Invalid values and the rest of their lines are kept as they are in `UNKNOWN` nodes,
+/// which become [`Unknown`](dom::node::Unknown) nodes in the DOM.
+/// Other invalid lines, e.g. table headers, are also kept in `UNKNOWN` nodes
+/// but they are not part of the DOM.
+/// The formatter does not change the text of `UNKNOWN` nodes.
+///
+/// Unknown constructs end at the end of their line unless they contain unclosed brackets.
+/// The errors in them are moved to [`Parse::unknown_errors`], so mistakes
+/// can still be shown to the user, errors in comments are still reported.
+pub fn parse_preserving_unknown(source: &str) -> Parse {
+    let parse = parse(source);
+
+    if parse.errors.is_empty() {
+        return parse;
+    }
+
+    let errors = parse.errors.clone();
+    let (unknown, unknown_errors) = unknown::unknown_ranges(source, &parse.into_syntax(), &errors);
+
+    let mut parser = Parser::new(source);
+    parser.unknown = unknown.clone();
+    let mut parse = parser.parse();
+
+    parse.errors.retain(|err| {
+        !unknown.iter().any(|r| {
+            r.start <= usize::from(err.range.start()) && usize::from(err.range.end()) <= r.end
+        })
+    });
+    parse.unknown_errors = unknown_errors;
+
+    parse
+}
+
 /// A hand-written parser that uses the Logos lexer
 /// to tokenize the source, then constructs
 /// a Rowan green tree from them.
@@ -69,9 +115,13 @@ pub(crate) struct Parser<'p> {
     //      special cases.
     error_whitelist: u16,
 
+    // Ranges of constructs that are preserved without parsing them.
+    unknown: Vec<Range<usize>>,
+
     lexer: Lexer<'p, SyntaxKind>,
     builder: GreenNodeBuilder<'p>,
     errors: Vec<Error>,
+    directives: Vec<Directive>,
 }
 
 impl<'p> Parser<'p> {
@@ -86,6 +136,8 @@ impl<'p> Parser<'p> {
         Parse {
             green_node: self.builder.finish(),
             errors: self.errors,
+            unknown_errors: Vec::new(),
+            directives: self.directives,
         }
     }
 }
@@ -105,9 +157,11 @@ impl<'p> Parser<'p> {
             skip_whitespace: true,
             key_pattern_syntax: false,
             error_whitelist: 0,
+            unknown: Vec::new(),
             lexer: SyntaxKind::lexer(source),
             builder: Default::default(),
             errors: Default::default(),
+            directives: Default::default(),
         }
     }
 
@@ -117,10 +171,12 @@ impl<'p> Parser<'p> {
         Parse {
             green_node: self.builder.finish(),
             errors: self.errors,
+            unknown_errors: Vec::new(),
+            directives: self.directives,
         }
     }
 
-    fn error(&mut self, message: &str) -> ParserResult<()> {
+    fn error(&mut self, message: Message) -> ParserResult<()> {
         let span = self.lexer.span();
 
         let err = Error {
@@ -128,7 +184,7 @@ impl<'p> Parser<'p> {
                 TextSize::from(span.start as u32),
                 TextSize::from(span.end as u32),
             ),
-            message: message.into(),
+            message,
         };
 
         let same_error = self
@@ -138,13 +194,7 @@ impl<'p> Parser<'p> {
             .unwrap_or(false);
 
         if !same_error {
-            self.add_error(&Error {
-                range: TextRange::new(
-                    TextSize::from(span.start as u32),
-                    TextSize::from(span.end as u32),
-                ),
-                message: message.into(),
-            });
+            self.add_error(&err);
             if let Some(t) = self.current_token {
                 if !self.whitelisted(t) {
                     self.token_as(ERROR).ok();
@@ -158,14 +208,14 @@ impl<'p> Parser<'p> {
     }
 
     // report error without consuming the current the token
-    fn report_error(&mut self, message: &str) -> ParserResult<()> {
+    fn report_error(&mut self, message: Message) -> ParserResult<()> {
         let span = self.lexer.span();
         self.add_error(&Error {
             range: TextRange::new(
                 TextSize::from(span.start as u32),
                 TextSize::from(span.end as u32),
             ),
-            message: message.into(),
+            message,
         });
         Err(())
     }
@@ -199,7 +249,7 @@ impl<'p> Parser<'p> {
         self.builder.token(kind.into(), s)
     }
 
-    fn must_token_or(&mut self, kind: SyntaxKind, message: &str) -> ParserResult<()> {
+    fn must_token_or(&mut self, kind: SyntaxKind, message: Message) -> ParserResult<()> {
         match self.get_token() {
             Ok(t) => {
                 if kind == t {
@@ -214,7 +264,7 @@ impl<'p> Parser<'p> {
                         self.lexer.span().start.try_into().unwrap(),
                         self.lexer.span().end.try_into().unwrap(),
                     ),
-                    message: "unexpected EOF".into(),
+                    message: Message::new("unexpected-eof"),
                 });
                 Err(())
             }
@@ -275,12 +325,24 @@ impl<'p> Parser<'p> {
                                         (self.lexer.span().start + e).try_into().unwrap(),
                                         (self.lexer.span().start + e).try_into().unwrap(),
                                     ),
-                                    message: "invalid character in comment".into(),
+                                    message: Message::new("invalid-comment-character"),
                                 });
                             }
                         }
                     };
 
+                    let span = self.lexer.span();
+                    if let Some(directive) = Directive::parse(
+                        self.lexer.slice(),
+                        TextRange::new(
+                            span.start.try_into().unwrap(),
+                            span.end.try_into().unwrap(),
+                        ),
+                        self.lexer.remainder(),
+                    ) {
+                        self.directives.push(directive);
+                    }
+
                     self.insert_token(token, self.lexer.slice());
                 }
                 WHITESPACE => {
@@ -291,7 +353,8 @@ impl<'p> Parser<'p> {
                         break;
                     }
                 }
-                ERROR => {
+                // Invalid tokens can start unknown constructs.
+                ERROR if self.unknown_end().is_none() => {
                     self.insert_token(token, self.lexer.slice());
                     let span = self.lexer.span();
                     self.add_error(&Error {
@@ -299,7 +362,7 @@ impl<'p> Parser<'p> {
                             span.start.try_into().unwrap(),
                             span.end.try_into().unwrap(),
                         ),
-                        message: "unexpected token".into(),
+                        message: Message::new("unexpected-token"),
                     })
                 }
                 _ => {
@@ -318,6 +381,36 @@ impl<'p> Parser<'p> {
         self.current_token.ok_or(())
     }
 
+    /// The end of the unknown construct that contains the current token, if any.
+    fn unknown_end(&self) -> Option<usize> {
+        let start = self.lexer.span().start;
+
+        self.unknown
+            .iter()
+            .find(|r| r.contains(&start))
+            .map(|r| r.end)
+    }
+
+    /// Add the tokens until the end of the unknown construct without parsing them.
+    fn parse_unknown(&mut self, end: usize) -> ParserResult<()> {
+        while let Ok(token) = self.get_token() {
+            if self.lexer.span().start >= end {
+                break;
+            }
+
+            self.builder.token(token.into(), self.lexer.slice());
+            self.current_token = None;
+
+            if self.lexer.span().end >= end {
+                break;
+            }
+
+            self.step();
+        }
+
+        Ok(())
+    }
+
     fn parse_root(&mut self) -> ParserResult<()> {
         // Ensure we have newlines between entries
         let mut not_newline = false;
@@ -327,6 +420,17 @@ impl<'p> Parser<'p> {
         let mut entry_started = false;
 
         while let Ok(token) = self.get_token() {
+            if let Some(end) = self.unknown_end() {
+                if entry_started {
+                    self.builder.finish_node();
+                    entry_started = false;
+                }
+
+                not_newline = true;
+                let _ = with_node!(self.builder, UNKNOWN, self.parse_unknown(end));
+                continue;
+            }
+
             match token {
                 BRACKET_START => {
                     if entry_started {
@@ -335,7 +439,7 @@ impl<'p> Parser<'p> {
                     }
 
                     if not_newline {
-                        let _ = self.error("expected new line");
+                        let _ = self.error(Message::new("expected-new-line"));
                         continue;
                     }
 
@@ -369,7 +473,7 @@ impl<'p> Parser<'p> {
                 }
                 _ => {
                     if not_newline {
-                        let _ = self.error("expected new line");
+                        let _ = self.error(Message::new("expected-new-line"));
                         continue;
                     }
                     if entry_started {
@@ -390,21 +494,36 @@ impl<'p> Parser<'p> {
     }
 
     fn parse_table_header(&mut self) -> ParserResult<()> {
-        self.must_token_or(BRACKET_START, r#"expected "[""#)?;
+        self.must_token_or(
+            BRACKET_START,
+            Message::new("expected-symbol").with_arg("symbol", "["),
+        )?;
         let _ = with_node!(self.builder, KEY, self.parse_key());
-        self.must_token_or(BRACKET_END, r#"expected "]""#)?;
+        self.must_token_or(
+            BRACKET_END,
+            Message::new("expected-symbol").with_arg("symbol", "]"),
+        )?;
 
         Ok(())
     }
 
     fn parse_table_array_header(&mut self) -> ParserResult<()> {
         self.skip_whitespace = false;
-        self.must_token_or(BRACKET_START, r#"expected "[[""#)?;
-        self.must_token_or(BRACKET_START, r#"expected "[[""#)?;
+        self.must_token_or(
+            BRACKET_START,
+            Message::new("expected-symbol").with_arg("symbol", "[["),
+        )?;
+        self.must_token_or(
+            BRACKET_START,
+            Message::new("expected-symbol").with_arg("symbol", "[["),
+        )?;
         self.skip_whitespace = true;
         let _ = with_node!(self.builder, KEY, self.parse_key());
         self.skip_whitespace = false;
-        let _ = self.must_token_or(BRACKET_END, r#"expected "]]""#);
+        let _ = self.must_token_or(
+            BRACKET_END,
+            Message::new("expected-symbol").with_arg("symbol", "]]"),
+        );
 
         // Hack in order to avoid calling `step` after
         // the second closing bracket.
@@ -414,7 +533,7 @@ impl<'p> Parser<'p> {
                 self.token_as_no_step(token)?;
             }
             _ => {
-                self.error(r#"expected "]]"#)?;
+                self.error(Message::new("expected-symbol").with_arg("symbol", "]]"))?;
             }
         }
         self.skip_whitespace = true;
@@ -426,7 +545,7 @@ impl<'p> Parser<'p> {
 
     fn parse_entry(&mut self) -> ParserResult<()> {
         with_node!(self.builder, KEY, self.parse_key())?;
-        self.must_token_or(EQ, r#"expected "=""#)?;
+        self.must_token_or(EQ, Message::new("expected-symbol").with_arg("symbol", "="))?;
         with_node!(self.builder, VALUE, self.parse_value())?;
 
         Ok(())
@@ -434,7 +553,7 @@ impl<'p> Parser<'p> {
 
     fn parse_key(&mut self) -> ParserResult<()> {
         if self.parse_ident().is_err() {
-            return self.report_error("expected identifier");
+            return self.report_error(Message::new("expected-identifier"));
         }
 
         let mut after_period = false;
@@ -445,14 +564,15 @@ impl<'p> Parser<'p> {
                     if !after_period {
                         return Ok(());
                     }
-                    return self.error("unexpected end of input");
+                    return self.error(Message::new("unexpected-eof"));
                 }
             };
 
             match t {
                 PERIOD => {
                     if after_period {
-                        return self.error(r#"unexpected ".""#);
+                        return self
+                            .error(Message::new("unexpected-symbol").with_arg("symbol", "."));
                     } else {
                         self.token()?;
                         after_period = true;
@@ -463,13 +583,13 @@ impl<'p> Parser<'p> {
 
                     match self.parse_ident() {
                         Ok(_) => {}
-                        Err(_) => return self.error("expected identifier"),
+                        Err(_) => return self.error(Message::new("expected-identifier")),
                     }
 
                     let token = self.get_token()?;
 
                     if !matches!(token, BRACKET_END) {
-                        self.error(r#"expected "]""#)?;
+                        self.error(Message::new("expected-symbol").with_arg("symbol", "]"))?;
                     }
                     self.step();
                     after_period = false;
@@ -478,11 +598,13 @@ impl<'p> Parser<'p> {
                     if after_period {
                         match self.parse_ident() {
                             Ok(_) => {}
-                            Err(_) => return self.report_error("expected identifier"),
+                            Err(_) => {
+                                return self.report_error(Message::new("expected-identifier"))
+                            }
                         }
                         after_period = false;
                     } else if self.key_pattern_syntax {
-                        return self.error("unexpected identifier");
+                        return self.error(Message::new("unexpected-identifier"));
                     } else {
                         break;
                     }
@@ -501,7 +623,7 @@ impl<'p> Parser<'p> {
                 if self.key_pattern_syntax {
                     self.token_as(IDENT)
                 } else {
-                    self.error("expected identifier")
+                    self.error(Message::new("expected-identifier"))
                 }
             }
             INTEGER_HEX | INTEGER_BIN | INTEGER_OCT => self.token_as(IDENT),
@@ -522,7 +644,7 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid control character in string literal".into(),
+                                message: Message::new("invalid-control-character"),
                             });
                         }
                     }
@@ -540,22 +662,22 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid character in string".into(),
+                                message: Message::new("invalid-string-character"),
                             });
                         }
                     }
                 };
 
-                match check_escape(self.lexer.slice()) {
+                match check_escape_ranges(self.lexer.slice()) {
                     Ok(_) => self.token_as(IDENT),
-                    Err(err_indices) => {
-                        for e in err_indices {
+                    Err(err_ranges) => {
+                        for e in err_ranges {
                             self.add_error(&Error {
                                 range: TextRange::new(
-                                    (self.lexer.span().start + e).try_into().unwrap(),
-                                    (self.lexer.span().start + e).try_into().unwrap(),
+                                    (self.lexer.span().start + e.start).try_into().unwrap(),
+                                    (self.lexer.span().start + e.end).try_into().unwrap(),
                                 ),
-                                message: "invalid escape sequence".into(),
+                                message: Message::new("invalid-escape-sequence"),
                             });
                         }
 
@@ -568,7 +690,7 @@ impl<'p> Parser<'p> {
             }
             FLOAT => {
                 if self.lexer.slice().starts_with('0') {
-                    self.error("zero-padded numbers are not allowed")
+                    self.error(Message::new("zero-padded-number"))
                 } else if self.lexer.slice().starts_with('+') {
                     Err(())
                 } else {
@@ -584,16 +706,20 @@ impl<'p> Parser<'p> {
                 }
             }
             BOOL => self.token_as(IDENT),
-            _ => self.error("expected identifier"),
+            _ => self.error(Message::new("expected-identifier")),
         }
     }
 
     fn parse_value(&mut self) -> ParserResult<()> {
         let t = match self.get_token() {
             Ok(t) => t,
-            Err(_) => return self.error("expected value"),
+            Err(_) => return self.error(Message::new("expected-value")),
         };
 
+        if let Some(end) = self.unknown_end() {
+            return with_node!(self.builder, UNKNOWN, self.parse_unknown(end));
+        }
+
         match t {
             BOOL | DATE_TIME_OFFSET | DATE_TIME_LOCAL | DATE | TIME => self.token(),
             INTEGER => {
@@ -613,30 +739,30 @@ impl<'p> Parser<'p> {
                     || (self.lexer.slice().starts_with("+0") && self.lexer.slice() != "+0")
                     || (self.lexer.slice().starts_with("-0") && self.lexer.slice() != "-0")
                 {
-                    self.error("zero-padded integers are not allowed")
+                    self.error(Message::new("zero-padded-integer"))
                 } else if !check_underscores(self.lexer.slice(), 10) {
-                    self.error("invalid underscores")
+                    self.error(Message::new("invalid-underscores"))
                 } else {
                     self.token()
                 }
             }
             INTEGER_BIN => {
                 if !check_underscores(self.lexer.slice(), 2) {
-                    self.error("invalid underscores")
+                    self.error(Message::new("invalid-underscores"))
                 } else {
                     self.token()
                 }
             }
             INTEGER_HEX => {
                 if !check_underscores(self.lexer.slice(), 16) {
-                    self.error("invalid underscores")
+                    self.error(Message::new("invalid-underscores"))
                 } else {
                     self.token()
                 }
             }
             INTEGER_OCT => {
                 if !check_underscores(self.lexer.slice(), 8) {
-                    self.error("invalid underscores")
+                    self.error(Message::new("invalid-underscores"))
                 } else {
                     self.token()
                 }
@@ -657,9 +783,9 @@ impl<'p> Parser<'p> {
                     || (int_slice.starts_with("+0") && int_slice != "+0")
                     || (int_slice.starts_with("-0") && int_slice != "-0")
                 {
-                    self.error("zero-padded numbers are not allowed")
+                    self.error(Message::new("zero-padded-number"))
                 } else if !check_underscores(self.lexer.slice(), 10) {
-                    self.error("invalid underscores")
+                    self.error(Message::new("invalid-underscores"))
                 } else {
                     self.token()
                 }
@@ -674,7 +800,7 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid control character in string literal".into(),
+                                message: Message::new("invalid-control-character"),
                             });
                         }
                     }
@@ -691,7 +817,7 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid character in string".into(),
+                                message: Message::new("invalid-string-character"),
                             });
                         }
                     }
@@ -708,22 +834,22 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid character in string".into(),
+                                message: Message::new("invalid-string-character"),
                             });
                         }
                     }
                 };
 
-                match check_escape(self.lexer.slice()) {
+                match check_escape_ranges(self.lexer.slice()) {
                     Ok(_) => self.token(),
-                    Err(err_indices) => {
-                        for e in err_indices {
+                    Err(err_ranges) => {
+                        for e in err_ranges {
                             self.add_error(&Error {
                                 range: TextRange::new(
-                                    (self.lexer.span().start + e).try_into().unwrap(),
-                                    (self.lexer.span().start + e).try_into().unwrap(),
+                                    (self.lexer.span().start + e.start).try_into().unwrap(),
+                                    (self.lexer.span().start + e.end).try_into().unwrap(),
                                 ),
-                                message: "invalid escape sequence".into(),
+                                message: Message::new("invalid-escape-sequence"),
                             });
                         }
 
@@ -744,22 +870,22 @@ impl<'p> Parser<'p> {
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                     (self.lexer.span().start + e).try_into().unwrap(),
                                 ),
-                                message: "invalid character in string".into(),
+                                message: Message::new("invalid-string-character"),
                             });
                         }
                     }
                 };
 
-                match check_escape(self.lexer.slice()) {
+                match check_escape_ranges(self.lexer.slice()) {
                     Ok(_) => self.token(),
-                    Err(err_indices) => {
-                        for e in err_indices {
+                    Err(err_ranges) => {
+                        for e in err_ranges {
                             self.add_error(&Error {
                                 range: TextRange::new(
-                                    (self.lexer.span().start + e).try_into().unwrap(),
-                                    (self.lexer.span().start + e).try_into().unwrap(),
+                                    (self.lexer.span().start + e.start).try_into().unwrap(),
+                                    (self.lexer.span().start + e.end).try_into().unwrap(),
                                 ),
-                                message: "invalid escape sequence".into(),
+                                message: Message::new("invalid-escape-sequence"),
                             });
                         }
 
@@ -778,15 +904,18 @@ impl<'p> Parser<'p> {
             }
             IDENT | BRACE_END => {
                 // FIXME(bit_flags): This branch is just a workaround.
-                self.report_error("expected value").ok();
+                self.report_error(Message::new("expected-value")).ok();
                 Ok(())
             }
-            _ => self.error("expected value"),
+            _ => self.error(Message::new("expected-value")),
         }
     }
 
     fn parse_inline_table(&mut self) -> ParserResult<()> {
-        self.must_token_or(BRACE_START, r#"expected "{""#)?;
+        self.must_token_or(
+            BRACE_START,
+            Message::new("expected-symbol").with_arg("symbol", "{"),
+        )?;
 
         let mut first = true;
         let mut comma_last = false;
@@ -795,7 +924,10 @@ impl<'p> Parser<'p> {
         loop {
             let t = match self.get_token() {
                 Ok(t) => t,
-                Err(_) => return self.report_error(r#"expected "}""#),
+                Err(_) => {
+                    return self
+                        .report_error(Message::new("expected-symbol").with_arg("symbol", "}"))
+                }
             };
 
             match t {
@@ -804,7 +936,7 @@ impl<'p> Parser<'p> {
                         // it is still reported as a syntax error,
                         // but we can still analyze it as if it was a valid
                         // table.
-                        let _ = self.report_error("expected value, trailing comma is not allowed");
+                        let _ = self.report_error(Message::new("trailing-comma"));
                     }
                     break self.add_token()?;
                 }
@@ -815,16 +947,19 @@ impl<'p> Parser<'p> {
                         break;
                     }
 
-                    let _ = self.error("newline is not allowed in an inline table");
+                    let _ = self.error(Message::new("inline-table-new-line"));
                     was_newline = true;
                 }
                 COMMA => {
                     if comma_last {
-                        let _ = self.report_error(r#"unexpected ",""#);
+                        let _ = self.report_error(
+                            Message::new("unexpected-symbol").with_arg("symbol", ","),
+                        );
                     }
 
                     if first {
-                        let _ = self.error(r#"unexpected ",""#);
+                        let _ =
+                            self.error(Message::new("unexpected-symbol").with_arg("symbol", ","));
                     } else {
                         self.token()?;
                     }
@@ -834,7 +969,7 @@ impl<'p> Parser<'p> {
                 _ => {
                     was_newline = false;
                     if !comma_last && !first {
-                        let _ = self.error(r#"expected ",""#);
+                        let _ = self.error(Message::new("expected-symbol").with_arg("symbol", ","));
                     }
                     let _ = whitelisted!(
                         self,
@@ -851,7 +986,10 @@ impl<'p> Parser<'p> {
     }
 
     fn parse_array(&mut self) -> ParserResult<()> {
-        self.must_token_or(BRACKET_START, r#"expected "[""#)?;
+        self.must_token_or(
+            BRACKET_START,
+            Message::new("expected-symbol").with_arg("symbol", "["),
+        )?;
 
         let mut first = true;
         let mut comma_last = false;
@@ -859,7 +997,7 @@ impl<'p> Parser<'p> {
             let t = match self.get_token() {
                 Ok(t) => t,
                 Err(_) => {
-                    let _ = self.report_error("unexpected EOF");
+                    let _ = self.report_error(Message::new("unexpected-eof"));
                     return Err(());
                 }
             };
@@ -872,14 +1010,15 @@ impl<'p> Parser<'p> {
                 }
                 COMMA => {
                     if first || comma_last {
-                        let _ = self.error(r#"unexpected ",""#);
+                        let _ =
+                            self.error(Message::new("unexpected-symbol").with_arg("symbol", ","));
                     }
                     self.token()?;
                     comma_last = true;
                 }
                 _ => {
                     if !comma_last && !first {
-                        let _ = self.error(r#"expected ",""#);
+                        let _ = self.error(Message::new("expected-symbol").with_arg("symbol", ","));
                     }
                     let _ = whitelisted!(
                         self,
@@ -923,6 +1062,11 @@ fn check_underscores(s: &str, radix: u32) -> bool {
 pub struct Parse {
     pub green_node: GreenNode,
     pub errors: Vec<Error>,
+    /// The syntax errors in the constructs that were preserved
+    /// by [`parse_preserving_unknown`], they are not in `errors`.
+    pub unknown_errors: Vec<Error>,
+    /// The `#:taplo` comments that control diagnostics.
+    pub directives: Vec<Directive>,
 }
 
 impl Parse {
@@ -938,4 +1082,13 @@ impl Parse {
     pub fn into_dom(self) -> dom::node::Node {
         dom::Node::from_syntax(self.into_syntax().into())
     }
+
+    /// Turn the parse into a DOM tree and the index of its keys.
+    ///
+    /// The index is built while creating the DOM tree,
+    /// so this is cheaper than creating both separately.
+    pub fn into_dom_with_index(self) -> (dom::node::Node, dom::index::KeyIndex) {
+        let (root, index) = dom::from_syntax::root_with_index(self.into_syntax().into());
+        (root.into(), index)
+    }
 }
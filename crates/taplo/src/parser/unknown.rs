@@ -0,0 +1,146 @@
+//! Finding the parts of a document that are preserved as unknown constructs.
+
+use super::Error;
+use crate::syntax::{SyntaxElement, SyntaxKind, SyntaxKind::*, SyntaxNode};
+use logos::Logos;
+use rowan::TextSize;
+use std::ops::Range;
+
+/// The ranges of the values and lines that contain the syntax errors,
+/// and the errors that are preserved in them.
+///
+/// The ranges are sorted and do not overlap, errors in comments
+/// and whitespace are not included.
+/// An error can be outside of its range, e.g. at the end of the input
+/// for an unclosed array.
+pub(super) fn unknown_ranges(
+    source: &str,
+    root: &SyntaxNode,
+    errors: &[Error],
+) -> (Vec<Range<usize>>, Vec<Error>) {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut unknown_errors = Vec::new();
+
+    for error in errors {
+        let start = match unknown_start(root, error.range.start()) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        if ranges.iter().any(|r| r.contains(&start)) {
+            unknown_errors.push(error.clone());
+            continue;
+        }
+
+        let end = unknown_end(source, start);
+        if end > start {
+            ranges.push(start..end);
+            unknown_errors.push(error.clone());
+        }
+    }
+
+    ranges.sort_by_key(|r| r.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start < last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    (merged, unknown_errors)
+}
+
+/// The start of the value or the line that contains the offset.
+///
+/// Values are preserved only if the key and the `=` of their entry are valid,
+/// otherwise the entire line is.
+fn unknown_start(root: &SyntaxNode, offset: TextSize) -> Option<usize> {
+    let element = root
+        .children_with_tokens()
+        .find(|c| c.text_range().contains(offset))
+        .or_else(|| {
+            root.children_with_tokens()
+                .filter(|c| c.text_range().end() == offset && !is_trivia(c))
+                .last()
+        })?;
+
+    // Unexpected line breaks belong to the construct before them.
+    let element = match element.kind() {
+        NEWLINE | WHITESPACE => {
+            let mut prev = element.prev_sibling_or_token();
+            while let Some(WHITESPACE) = prev.as_ref().map(|p| p.kind()) {
+                prev = prev.and_then(|p| p.prev_sibling_or_token());
+            }
+            prev?
+        }
+        _ => element,
+    };
+
+    if is_trivia(&element) {
+        return None;
+    }
+
+    if let Some(entry) = element.as_node().filter(|n| n.kind() == ENTRY) {
+        let has_key = entry.children().any(|n| n.kind() == KEY);
+        let eq = entry.children_with_tokens().find(|c| c.kind() == EQ);
+
+        // The value starts at the first token after the `=`,
+        // which can also be an invalid token.
+        let value = eq
+            .filter(|eq| has_key && offset >= eq.text_range().end())
+            .and_then(|eq| {
+                std::iter::successors(eq.next_sibling_or_token(), |c| c.next_sibling_or_token())
+                    .find(|c| c.kind() != WHITESPACE && !c.text_range().is_empty())
+            })
+            .filter(|value| !is_trivia(value));
+
+        if let Some(value) = value {
+            return Some(value.text_range().start().into());
+        }
+    }
+
+    Some(element.text_range().start().into())
+}
+
+/// The end of the unknown construct at the given offset.
+///
+/// It ends at the end of the line unless there are unclosed brackets or braces,
+/// in which case it spans multiple lines until they are closed.
+/// A line starting with `[` always ends it, as it is most likely a table header.
+fn unknown_end(source: &str, start: usize) -> usize {
+    let mut lexer = SyntaxKind::lexer(&source[start..]);
+    let mut depth = 0_usize;
+    let mut line_start = false;
+    let mut end = 0;
+
+    while let Some(token) = lexer.next() {
+        match token {
+            NEWLINE if depth == 0 => break,
+            NEWLINE => {
+                line_start = true;
+                continue;
+            }
+            // Indented table headers still start a line.
+            WHITESPACE => continue,
+            COMMENT => {
+                line_start = false;
+                continue;
+            }
+            BRACKET_START if line_start => break,
+            BRACKET_START | BRACE_START => depth += 1,
+            BRACKET_END | BRACE_END => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+
+        line_start = false;
+        end = lexer.span().end;
+    }
+
+    start + end
+}
+
+fn is_trivia(element: &SyntaxElement) -> bool {
+    matches!(element.kind(), WHITESPACE | NEWLINE | COMMENT)
+}
@@ -99,6 +99,7 @@ pub enum SyntaxKind {
     ENTRY,              // e.g.: key = "value"
     ARRAY,              // e.g.: [ 1, 2 ]
     INLINE_TABLE,       // e.g.: { key = "value" }
+    UNKNOWN,            // a construct that is preserved as it is
 
     ROOT, // root node
 }
@@ -182,7 +183,7 @@ fn lex_multi_line_string(lex: &mut Lexer<SyntaxKind>) -> bool {
         total_len += c.len_utf8();
 
         if c == '\\' {
-            escaped = true;
+            escaped = !escaped;
             continue;
         }
 
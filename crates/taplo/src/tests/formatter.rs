@@ -1108,3 +1108,293 @@ my_array = [
 
     assert_format!(expected, &formatted);
 }
+
+/// Type `ch` at the position of `|` and apply the resulting edits.
+fn type_char(src: &str, ch: char, options: formatter::Options) -> String {
+    let offset = src.find('|').unwrap();
+    let mut src = src.replacen('|', &ch.to_string(), 1);
+
+    let mut edits = formatter::format_on_type(&src, ((offset + 1) as u32).into(), ch, options);
+    edits.sort_by_key(|edit| edit.range.start());
+
+    for edit in edits.into_iter().rev() {
+        src.replace_range(
+            usize::from(edit.range.start())..usize::from(edit.range.end()),
+            &edit.text,
+        );
+    }
+
+    src
+}
+
+#[test]
+fn on_type_close_array_header() {
+    assert_eq!(type_char("[[bin|\n", ']', Default::default()), "[[bin]]\n");
+    assert_eq!(type_char("[[bin|]\n", ']', Default::default()), "[[bin]]\n");
+    assert_eq!(type_char("[bin|\n", ']', Default::default()), "[bin]\n");
+    assert_eq!(
+        type_char("a = [[1|\n", ']', Default::default()),
+        "a = [[1]\n"
+    );
+}
+
+#[test]
+fn on_type_array_newline() {
+    assert_eq!(
+        type_char("a = [\n  1|", '\n', Default::default()),
+        "a = [\n  1,\n  "
+    );
+    assert_eq!(
+        type_char(
+            "[table]\n  a = [ # comment\n    1 # one|\n",
+            '\n',
+            Default::default()
+        ),
+        "[table]\n  a = [ # comment\n    1, # one\n    \n"
+    );
+    assert_eq!(
+        type_char(
+            "a = [\n\t1,\n\t2|]",
+            '\n',
+            formatter::Options {
+                array_trailing_comma: false,
+                ..Default::default()
+            }
+        ),
+        "a = [\n\t1,\n\t2\n]"
+    );
+    assert_eq!(
+        type_char("a = { b = 1|", '\n', Default::default()),
+        "a = { b = 1\n"
+    );
+    assert_eq!(type_char("[table|", '\n', Default::default()), "[table\n");
+}
+
+#[test]
+fn on_type_entry_separator() {
+    assert_eq!(type_char("a|", '=', Default::default()), "a = ");
+    assert_eq!(type_char("a   | 1", '=', Default::default()), "a = 1");
+    assert_eq!(
+        type_char(
+            "a |1",
+            '=',
+            formatter::Options {
+                compact_entries: true,
+                ..Default::default()
+            }
+        ),
+        "a=1"
+    );
+    assert_eq!(type_char("a = \"b|", '=', Default::default()), "a = \"b=");
+}
+
+#[test]
+fn reflow_comments() {
+    let src = r#"#:schema ./schema.json
+# This is a long comment that does not fit in the configured column width at all.
+# It continues here.
+#
+# - A list item that is also too long to fit in a single line of the configured width.
+# - Short item.
+#   Continuation.
+#
+# ```
+# let code = "this is a fenced code block that is never reflowed regardless of its length";
+# ```
+#     indented code that is also never reflowed regardless of its length, it is code
+# Short comments
+# are not joined.
+
+[table]
+  # A comment in a table that is indented and too long for the configured width.
+  key = "value" # trailing comment
+"#;
+
+    let expected = r#"#:schema ./schema.json
+# This is a long comment that does not fit in the
+# configured column width at all. It continues here.
+#
+# - A list item that is also too long to fit in a
+#   single line of the configured width.
+# - Short item.
+#   Continuation.
+#
+# ```
+# let code = "this is a fenced code block that is never reflowed regardless of its length";
+# ```
+#     indented code that is also never reflowed regardless of its length, it is code
+# Short comments
+# are not joined.
+
+[table]
+  # A comment in a table that is indented and too
+  # long for the configured width.
+  key = "value" # trailing comment
+"#;
+
+    let options = formatter::Options {
+        reflow_comments: true,
+        column_width: 52,
+        indent_entries: true,
+        ..Default::default()
+    };
+
+    let formatted = crate::formatter::format(src, options.clone());
+    assert_format!(expected, &formatted);
+    let formatted = crate::formatter::format(&formatted, options);
+    assert_format!(expected, &formatted);
+
+    let formatted = crate::formatter::format(src, Default::default());
+    assert!(formatted.contains(
+        "# This is a long comment that does not fit in the configured column width at all.\n"
+    ));
+}
+
+#[test]
+fn align_across_blank_lines() {
+    let src = r#"
+name = "taplo" # name
+version = "0.1.0"
+
+
+edition = "2021" # edition
+# comment
+license_file = "LICENSE"
+
+[table]
+b = 1
+a = 22
+
+dd = 3
+c = 4
+"#;
+
+    let expected = r#"
+name    = "taplo" # name
+version = "0.1.0"
+
+edition = "2021"  # edition
+# comment
+license_file = "LICENSE"
+
+[table]
+a  = 22
+b  = 1
+
+c  = 4
+dd = 3
+"#;
+
+    let options = formatter::Options {
+        align_entries: true,
+        align_across_blank_lines: true,
+        allowed_blank_lines: 1,
+        reorder_keys: true,
+        ..Default::default()
+    };
+
+    let formatted = crate::formatter::format(src, options);
+    assert_format!(expected, &formatted);
+
+    let formatted = crate::formatter::format(
+        src,
+        formatter::Options {
+            align_entries: true,
+            ..Default::default()
+        },
+    );
+    assert!(formatted.starts_with(
+        "\nname    = \"taplo\" # name\nversion = \"0.1.0\"\n\n\nedition = \"2021\" # edition\n"
+    ));
+}
+
+#[test]
+fn custom_layout() {
+    use formatter::{ArrayStats, EntryStats, InlineTableStats, Layout};
+    use std::rc::Rc;
+
+    /// Arrays with more than two items on separate lines, regardless of the width.
+    #[derive(Debug)]
+    struct ItemCountLayout;
+
+    impl Layout for ItemCountLayout {
+        fn expand_entry(&self, _stats: &EntryStats, _options: &formatter::Options) -> bool {
+            false
+        }
+
+        fn multiline_array(&self, stats: &ArrayStats, _options: &formatter::Options) -> bool {
+            stats.item_count > 2
+        }
+
+        fn expand_inline_table(
+            &self,
+            _stats: &InlineTableStats,
+            _options: &formatter::Options,
+        ) -> bool {
+            false
+        }
+    }
+
+    let src = r#"short = [1, 2]
+long = [1, 2, 3]
+wide = ["aaaaaaaaaaaaaaaaaaaa", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb", "cccccccccccccccccccccccccc"]
+"#;
+
+    let expected = r#"short = [1, 2]
+long = [
+  1,
+  2,
+  3,
+]
+wide = [
+  "aaaaaaaaaaaaaaaaaaaa",
+  "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+  "cccccccccccccccccccccccccc",
+]
+"#;
+
+    let formatted =
+        formatter::format_with_layout(src, Default::default(), Rc::new(ItemCountLayout));
+    assert_format!(expected, &formatted);
+
+    let src = r#"pair = [1111111111111111111111111111111111111, 2222222222222222222222222222222222222222]
+"#;
+    let formatted =
+        formatter::format_with_layout(src, Default::default(), Rc::new(ItemCountLayout));
+    assert_format!(src, &formatted);
+}
+
+#[test]
+fn unknown_constructs() {
+    use crate::parser::parse_preserving_unknown;
+
+    let src = r#"
+a=1
+b  =  \x2A   # comment
+c = {
+  d = 1,
+}
+[table]
+  e="\e"
+@include    "other.toml"
+f=[1,2]
+"#;
+
+    let expected = r#"
+a = 1
+b = \x2A # comment
+c = {
+  d = 1,
+}
+[table]
+e = "\e"
+@include    "other.toml"
+f = [1, 2]
+"#;
+
+    let formatted = formatter::format_syntax(
+        parse_preserving_unknown(src).into_syntax(),
+        Default::default(),
+    );
+    assert_format!(expected, &formatted);
+}
@@ -27,3 +27,67 @@ fn comments_after_tables() {
 
     assert!(errors.is_empty(), "{:#?}", errors);
 }
+
+#[test]
+fn preserving_unknown() {
+    use crate::parser::parse_preserving_unknown;
+
+    let src = r#"a = 1
+b = \x2A # a value from the future
+c = {
+  d = 1,
+}
+
+[table]
+e = "\e"
+@include "other.toml"
+f = [1, 2]
+"#;
+
+    let p = parse_preserving_unknown(src);
+    assert!(p.errors.is_empty(), "{:#?}", p.errors);
+    assert!(!p.unknown_errors.is_empty());
+
+    let syntax = p.clone().into_syntax();
+    assert_eq!(syntax.to_string(), src);
+
+    let dom = p.into_dom();
+    assert!(dom.validate().is_ok());
+
+    assert_eq!(
+        dom.get("a").as_integer().unwrap().value().as_positive(),
+        Some(1)
+    );
+    assert_eq!(dom.get("b").as_unknown().unwrap().text(), r"\x2A");
+    assert_eq!(dom.get("c").as_unknown().unwrap().text(), "{\n  d = 1,\n}");
+    assert_eq!(
+        dom.get("table").get("e").as_unknown().unwrap().text(),
+        r#""\e""#
+    );
+    assert!(dom.get("table").get("f").is_array());
+
+    let src = "a = 1\nb = [1, 2\n";
+    let p = parse_preserving_unknown(src);
+    assert!(p.errors.is_empty(), "{:#?}", p.errors);
+    assert_eq!(p.unknown_errors.len(), 1);
+    assert_eq!(p.unknown_errors[0], parse(src).errors[0]);
+    assert_eq!(p.into_dom().get("b").as_unknown().unwrap().text(), "[1, 2");
+
+    let src = "a = [1, 2\n\n  [server]\n  port = 1\n";
+    let p = parse_preserving_unknown(src);
+    assert!(p.errors.is_empty(), "{:#?}", p.errors);
+    let dom = p.into_dom();
+    assert_eq!(dom.get("a").as_unknown().unwrap().text(), "[1, 2");
+    assert_eq!(
+        dom.get("server")
+            .get("port")
+            .as_integer()
+            .unwrap()
+            .value()
+            .as_positive(),
+        Some(1)
+    );
+
+    let errors = parse_preserving_unknown("a = 1 # \u{7}\n").errors;
+    assert_eq!(errors.len(), 1);
+}
@@ -1,4 +1,6 @@
+use crate::dom::node::StrRepr;
 use logos::{Lexer, Logos};
+use std::ops::Range;
 
 /// Escaping based on:
 ///
@@ -119,59 +121,159 @@ pub fn unescape(s: &str) -> Result<String, usize> {
 /// Same as unescape, but doesn't create a new
 /// unescaped string, and returns all invalid escape indices.
 pub fn check_escape(s: &str) -> Result<(), Vec<usize>> {
-    let mut lexer: Lexer<Escape> = Lexer::new(s);
-    let mut invalid = Vec::new();
+    check_escape_ranges(s).map_err(|invalid| invalid.into_iter().map(|r| r.start).collect())
+}
 
-    while let Some(t) = lexer.next() {
-        match t {
-            Backspace => {}
-            Tab => {}
-            LineFeed => {}
-            FormFeed => {}
-            CarriageReturn => {}
-            Quote => {}
-            Backslash => {}
-            Newline => {}
-            Unicode => {
-                let char_val = match u32::from_str_radix(&lexer.slice()[2..], 16) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        invalid.push(lexer.span().start);
-                        continue;
-                    }
-                };
+/// Same as [`check_escape`], but returns the ranges of the invalid escape sequences.
+pub(crate) fn check_escape_ranges(s: &str) -> Result<(), Vec<Range<usize>>> {
+    let invalid: Vec<_> = escaped_parts(s)
+        .filter(|(_, part)| matches!(part, EscapedPart::Invalid(_)))
+        .map(|(range, _)| range)
+        .collect();
 
-                match std::char::from_u32(char_val) {
-                    None => {
-                        invalid.push(lexer.span().start);
-                    }
-                    Some(_) => {}
-                };
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        Err(invalid)
+    }
+}
+
+/// Escape the string so that it can be the content of a string of the given kind,
+/// it only has to be surrounded by the quotes of the kind.
+///
+/// Returns [`None`] if a literal string cannot contain the value,
+/// e.g. a single quote or a newline in a [`Literal`](StrRepr::Literal) string.
+pub fn escape_for(s: &str, repr: StrRepr) -> Option<String> {
+    match repr {
+        StrRepr::Basic => Some(escape_basic(s, false)),
+        StrRepr::MultiLine => Some(escape_basic(s, true)),
+        StrRepr::Literal => {
+            if s.chars()
+                .any(|c| c == '\'' || (c != '\t' && c.is_control()))
+            {
+                return None;
             }
-            UnicodeLarge => {
-                let char_val = match u32::from_str_radix(&lexer.slice()[2..], 16) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        invalid.push(lexer.span().start);
-                        continue;
-                    }
-                };
 
-                match std::char::from_u32(char_val) {
-                    None => {
-                        invalid.push(lexer.span().start);
-                    }
-                    Some(_) => {}
-                };
+            Some(s.to_string())
+        }
+        StrRepr::MultiLineLiteral => {
+            let invalid_char = s
+                .replace("\r\n", "\n")
+                .chars()
+                .any(|c| c != '\t' && c != '\n' && c.is_control());
+
+            if invalid_char || s.contains("'''") {
+                return None;
+            }
+
+            // A newline right after the opening quotes is trimmed.
+            if s.starts_with('\n') || s.starts_with("\r\n") {
+                Some(format!("\n{s}"))
+            } else {
+                Some(s.to_string())
             }
-            Unknown => invalid.push(lexer.span().start),
-            UnEscaped => {}
         }
     }
+}
 
-    if invalid.is_empty() {
-        Ok(())
-    } else {
-        Err(invalid)
+fn escape_basic(s: &str, multi_line: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut quotes = 0;
+
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            // Three quotes in a row would end the string.
+            '"' if multi_line && quotes < 2 => {
+                quotes += 1;
+                escaped.push(c);
+                continue;
+            }
+            // A newline right after the opening quotes is trimmed.
+            '\n' if multi_line && i > 0 => escaped.push(c),
+            '\u{0008}' => escaped.push_str(r#"\b"#),
+            '\u{0009}' => escaped.push_str(r#"\t"#),
+            '\u{000A}' => escaped.push_str(r#"\n"#),
+            '\u{000C}' => escaped.push_str(r#"\f"#),
+            '\u{000D}' => escaped.push_str(r#"\r"#),
+            '\u{0022}' => escaped.push_str(r#"\""#),
+            '\u{005C}' => escaped.push_str(r#"\\"#),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+            _ => escaped.push(c),
+        }
+
+        quotes = 0;
     }
+
+    escaped
+}
+
+/// A part of an escaped string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EscapedPart<'s> {
+    /// Text that is not escaped.
+    Verbatim(&'s str),
+    /// An invalid escape sequence.
+    Invalid(&'s str),
+    /// A valid escape sequence and the character it stands for,
+    /// escaped newlines have no character.
+    Escape(Option<char>),
+}
+
+/// Split the string into verbatim text and escape sequences
+/// along with their ranges in the string.
+///
+/// Unlike [`unescape`], this does not fail on invalid escape sequences.
+pub(crate) fn escaped_parts(s: &str) -> impl Iterator<Item = (Range<usize>, EscapedPart<'_>)> {
+    let mut lexer: Lexer<Escape> = Lexer::new(s);
+    let mut end = 0;
+
+    core::iter::from_fn(move || {
+        let t = match lexer.next() {
+            Some(t) => t,
+            // The lexer can stop before the end, `unescape` keeps the rest too.
+            None if end < s.len() => {
+                let rest = end..s.len();
+                end = s.len();
+                return Some((rest.clone(), EscapedPart::Verbatim(&s[rest])));
+            }
+            None => return None,
+        };
+
+        let part = match t {
+            Backspace => EscapedPart::Escape(Some('\u{0008}')),
+            Tab => EscapedPart::Escape(Some('\u{0009}')),
+            LineFeed => EscapedPart::Escape(Some('\u{000A}')),
+            FormFeed => EscapedPart::Escape(Some('\u{000C}')),
+            CarriageReturn => EscapedPart::Escape(Some('\u{000D}')),
+            Quote => EscapedPart::Escape(Some('\u{0022}')),
+            Backslash => EscapedPart::Escape(Some('\u{005C}')),
+            Newline => EscapedPart::Escape(None),
+            Unicode | UnicodeLarge => u32::from_str_radix(&lexer.slice()[2..], 16)
+                .ok()
+                .and_then(std::char::from_u32)
+                .map(|c| EscapedPart::Escape(Some(c)))
+                .unwrap_or_else(|| EscapedPart::Invalid(lexer.slice())),
+            Unknown => {
+                // Malformed unicode escapes include the digits after them.
+                let digits = match lexer.slice() {
+                    r"\u" => 4,
+                    r"\U" => 8,
+                    _ => 0,
+                };
+                let len = lexer
+                    .remainder()
+                    .chars()
+                    .take(digits)
+                    .take_while(char::is_ascii_alphanumeric)
+                    .count();
+                lexer.bump(len);
+
+                EscapedPart::Invalid(lexer.slice())
+            }
+            UnEscaped => EscapedPart::Verbatim(lexer.slice()),
+        };
+
+        end = lexer.span().end;
+        Some((lexer.span(), part))
+    })
 }
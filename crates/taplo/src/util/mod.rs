@@ -9,7 +9,9 @@ mod escape;
 pub mod syntax;
 
 pub use escape::check_escape;
-pub use escape::{escape, unescape};
+pub(crate) use escape::check_escape_ranges;
+pub use escape::{escape, escape_for, unescape};
+pub(crate) use escape::{escaped_parts, EscapedPart};
 
 pub(crate) mod allowed_chars {
     pub(crate) fn comment(s: &str) -> Result<(), Vec<usize>> {
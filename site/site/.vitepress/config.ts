@@ -96,6 +96,14 @@ export default defineConfigWithTheme<DefaultTheme.Config>({
               text: "Conversion and Extraction",
               link: "/cli/usage/conversion-and-extraction",
             },
+            {
+              text: "Replacing Values",
+              link: "/cli/usage/replacing",
+            },
+            {
+              text: "Git Integration",
+              link: "/cli/usage/git",
+            },
             {
               text: "Language Server",
               link: "/cli/usage/language-server",